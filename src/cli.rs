@@ -1,9 +1,29 @@
+use crate::common::{format_bytes, in_cwd};
+use crate::config_resolution::{self, ConfigFile, Resolution};
+use crate::credentials::{self, Credential, Store};
 use crate::hwconfig;
+use crate::hwconfig_diff::{self, Mode};
+use crate::hwconfig_parser;
+use crate::hwconfig_profiles;
+use crate::ion_catalog::{Catalog, CatalogKind, Validation};
+use crate::ion_diagnostics::{self, OperationsInstance, SettingsInstance};
 use crate::logging;
+use crate::remotes;
 use crate::report;
-use crate::versions;
-use std::path::Path;
+use crate::report_archive::{self, ApplyTarget, ReportInfo};
+use crate::report_compare;
+use crate::report_encryption::{self, ENCRYPTED_EXTENSION};
+use crate::report_format::ArchiveFormat;
+use crate::report_upload::UploadProperties;
+use crate::report_watch;
+use crate::report_profile::{self, Collector, ReportProfile};
+use crate::versions::{self, UploadStatus};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use structopt::StructOpt;
+use strum::IntoEnumIterator;
 
 #[derive(StructOpt, Debug)]
 #[structopt(
@@ -13,9 +33,14 @@ use structopt::StructOpt;
 pub struct Sgt {
     #[structopt(subcommand)]
     pub command: Option<Command>,
+    #[structopt(long, global = true, about = "Artifactory remote from the settings file. Overrides SGT_REMOTE.")]
+    pub remote: Option<String>,
+    #[structopt(long, global = true, about = "Replaces the remote's URL. Overrides SGT_ARTIFACTORY_URL.")]
+    pub artifactory_url: Option<String>,
 }
 
 #[derive(StructOpt, Debug)]
+#[allow(clippy::large_enum_variant)] // Parsed once at startup.
 pub enum Command {
     #[structopt(about = "Run only the backend HTTP server.")]
     Backend,
@@ -25,10 +50,33 @@ pub enum Command {
     },
     #[structopt(name = "hwconfig", about = "Simulated hardware configuration.")]
     HwConfig(HwConfigCommand),
+    #[structopt(about = "Ion Diagnostics configuration.")]
+    Ion(IonCommand),
     #[structopt(about = "Logging configuration.")]
     Log(LogCommand),
     #[structopt(about = "Create or browse reports.")]
     Report(ReportCommand),
+    #[structopt(about = "List the Artifactory remotes, marking the one in use.")]
+    Remotes,
+    #[structopt(about = "Artifactory credentials for the remote in use.")]
+    Auth(AuthCommand),
+}
+
+#[derive(StructOpt, Debug)]
+pub enum AuthCommand {
+    #[structopt(about = "Save an access token, or a password or API key. Read from SGT_ARTIFACTORY_SECRET or the terminal.")]
+    Login {
+        #[structopt(long, about = "Basic authentication as this user, with their password.")]
+        user: Option<String>,
+        #[structopt(long, conflicts_with = "user", about = "Save an API key rather than an access token.")]
+        api_key: bool,
+        #[structopt(long, about = "keyring or file. Defaults to the file on Linux and the keyring elsewhere.")]
+        store: Option<Store>,
+    },
+    #[structopt(about = "Remove the saved credential.")]
+    Logout,
+    #[structopt(about = "Show the saved credential and whether the remote accepts it.")]
+    Status,
 }
 
 #[derive(StructOpt, Debug)]
@@ -38,29 +86,264 @@ pub enum LogCommand {
     SinkPath,
 }
 
+#[derive(StructOpt, Debug)]
+pub enum IonCommand {
+    Show,
+    Path,
+    #[structopt(about = "Scan state files and trace logs for setting paths and operation names.")]
+    Harvest {
+        #[structopt(parse(from_os_str), about = "Additional trace logs to scan.")]
+        logs: Vec<PathBuf>,
+    },
+    #[structopt(about = "List known entries. Kind is 'settings' or 'operations'.")]
+    Complete {
+        kind: CatalogKind,
+        prefix: Option<String>,
+    },
+    #[structopt(about = "Report setting paths and operation names missing from the catalogue.")]
+    Validate,
+    AddSetting {
+        paths: Vec<String>,
+        #[structopt(short, long, about = "Add even if not in the catalogue.")]
+        force: bool,
+    },
+    AddOperation {
+        names: Vec<String>,
+        #[structopt(short, long, about = "Add even if not in the catalogue.")]
+        force: bool,
+    },
+}
+
 #[derive(StructOpt, Debug)]
 pub enum ReportCommand {
+    #[structopt(about = "Create a report. Adds a numeric suffix if the file exists, unless --force is given.")]
     Zip {
         name: String,
         #[structopt(short, long, about = "Overwrite file if necessary.")]
         force: bool,
+        #[structopt(short, long, parse(from_os_str), about = "Directory or file to write the report to.")]
+        output: Option<PathBuf>,
+        #[structopt(long, about = "Encrypt with a passphrase, read from SGT_REPORT_PASSPHRASE or the terminal.")]
+        passphrase: bool,
+        #[structopt(flatten)]
+        profile: ProfileOptions,
+    },
+    #[structopt(about = "Decrypt an encrypted report, by default next to it.")]
+    Decrypt {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        #[structopt(short, long, parse(from_os_str), number_of_values = 1, about = "age identity file with the private key. Defaults to report_identity.txt in the toolkit directory.")]
+        identity: Vec<PathBuf>,
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
     },
+    #[structopt(about = "Show what a report would collect.")]
+    Collectors {
+        #[structopt(flatten)]
+        profile: ProfileOptions,
+    },
+    #[structopt(about = "Upload a report file, or one created today with the given name.")]
     Upload {
+        #[structopt(about = "Report file, or report name. With --create, the name of the report to create.")]
+        report: String,
+        #[structopt(long, about = "Create the report first, with the profile options.")]
+        create: bool,
+        #[structopt(long, about = "Ticket id to attach to the upload.")]
+        ticket: Option<String>,
+        #[structopt(long, about = "Who is reporting the problem. Defaults to the logged in user.")]
+        reporter: Option<String>,
+        #[structopt(long, about = "With --create, encrypt with a passphrase.")]
+        passphrase: bool,
+        #[structopt(flatten)]
+        profile: ProfileOptions,
+    },
+    #[structopt(about = "Reports uploaded to Artifactory.")]
+    Remote(RemoteCommand),
+    #[structopt(about = "Show the summary, versions and files of a report.")]
+    Inspect {
+        #[structopt(parse(from_os_str))]
+        zip: PathBuf,
+    },
+    #[structopt(about = "Extract a report, by default next to it.")]
+    Extract {
+        #[structopt(parse(from_os_str))]
+        zip: PathBuf,
+        #[structopt(parse(from_os_str))]
+        dir: Option<PathBuf>,
+    },
+    #[structopt(about = "Copy the configuration and state files in a report to where SigGen reads them.")]
+    Apply {
+        #[structopt(parse(from_os_str))]
+        zip: PathBuf,
+        #[structopt(long, use_delimiter = true, about = "Comma separated: logging, hwconfig, state. Default all.")]
+        only: Vec<ApplyTarget>,
+        #[structopt(long, about = "Print what would be done without changing anything.")]
+        dry_run: bool,
+        #[structopt(short, long, about = "Apply files even if they were redacted.")]
+        force: bool,
+    },
+    #[structopt(about = "Compare an older report with a newer one.")]
+    Compare {
+        #[structopt(parse(from_os_str))]
+        a: PathBuf,
+        #[structopt(parse(from_os_str))]
+        b: PathBuf,
+        #[structopt(long, about = "Print JSON instead of text.")]
+        json: bool,
+    },
+    #[structopt(about = "Create a report whenever an exception or matching log line is written. Runs until stopped.")]
+    Watch {
+        #[structopt(long, default_value = "5", about = "Seconds between checks.")]
+        interval: u64,
+        #[structopt(long, number_of_values = 1, about = "Text in a log line that triggers a report. Default [critical].")]
+        pattern: Vec<String>,
+        #[structopt(long, default_value = "10", about = "Minimum minutes between reports.")]
+        cooldown: u64,
+        #[structopt(long, default_value = "10", about = "Number of auto-generated reports to keep.")]
+        keep: usize,
+        #[structopt(long, about = "Upload each report.")]
+        upload: bool,
+        #[structopt(flatten)]
+        profile: ProfileOptions,
+    },
+    #[structopt(about = "Print a file from a report.")]
+    Cat {
+        #[structopt(parse(from_os_str))]
+        zip: PathBuf,
+        file: String,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+pub enum RemoteCommand {
+    #[structopt(about = "List uploaded reports, newest first.")]
+    List {
+        #[structopt(long, about = "Only reports uploaded from this host.")]
+        host: Option<String>,
+        #[structopt(long, about = "Only reports from this date on, e.g. 2024-03-01.")]
+        since: Option<chrono::NaiveDate>,
+    },
+    #[structopt(about = "Download an uploaded report into the working directory.")]
+    Get {
         name: String,
+        #[structopt(short, long, parse(from_os_str), about = "Directory or file to download to.")]
+        output: Option<PathBuf>,
     },
 }
 
+/// Adjustments to the saved report profile for a single run.
+#[derive(StructOpt, Debug)]
+pub struct ProfileOptions {
+    #[structopt(long, parse(from_os_str), about = "Report profile to start from instead of the saved one.")]
+    profile: Option<PathBuf>,
+    #[structopt(long, number_of_values = 1, about = "Enable a collector the profile excludes.")]
+    include: Vec<Collector>,
+    #[structopt(long, number_of_values = 1, about = "Disable a collector.")]
+    exclude: Vec<Collector>,
+    #[structopt(long, number_of_values = 1, about = "Also collect files matching a glob pattern.")]
+    extra: Vec<String>,
+    #[structopt(long, parse(try_from_str = report_profile::parse_size), about = "Skip files larger than this, e.g. 10M.")]
+    max_file_size: Option<u64>,
+    #[structopt(long, parse(try_from_str = report_profile::parse_size), about = "Limit the total size of collected files.")]
+    max_total_size: Option<u64>,
+    #[structopt(long, about = "Replace host names, user names, addresses and emails in collected text.")]
+    redact: bool,
+    #[structopt(long, about = "Days of daily log files to collect. Defaults to 7.")]
+    log_days: Option<u32>,
+    #[structopt(long, parse(try_from_str = report_profile::parse_size), about = "Limit the total size of log files. Defaults to 100M.")]
+    max_log_size: Option<u64>,
+    #[structopt(long, about = "zip, tar.gz or tar.zst.")]
+    format: Option<ArchiveFormat>,
+    #[structopt(long, about = "Compression level, 0-9 or 1-19 for tar.zst. Zip only tells 0, stored, from the other levels.")]
+    level: Option<u32>,
+    #[structopt(long, number_of_values = 1, about = "Encrypt to an age public key, or to the keys listed in a file.")]
+    encrypt_to: Vec<String>,
+}
+
+impl ProfileOptions {
+    fn resolve(self) -> anyhow::Result<ReportProfile> {
+        let path = self.profile.unwrap_or_else(report_profile::default_profile_path);
+        let mut profile = report_profile::load(&path)?;
+        for collector in self.include {
+            profile.set_enabled(collector, true);
+        }
+        for collector in self.exclude {
+            profile.set_enabled(collector, false);
+        }
+        profile.extra.extend(self.extra);
+        profile.max_file_size = self.max_file_size.or(profile.max_file_size);
+        profile.max_total_size = self.max_total_size.or(profile.max_total_size);
+        profile.redact |= self.redact;
+        profile.log_days = self.log_days.or(profile.log_days);
+        profile.max_log_size = self.max_log_size.or(profile.max_log_size);
+        profile.format = self.format.unwrap_or(profile.format);
+        profile.level = self.level.or(profile.level);
+        profile.encrypt_to.extend(self.encrypt_to);
+        Ok(profile)
+    }
+}
+
 #[derive(StructOpt, Debug)]
 pub enum HwConfigCommand {
-    Restore,
+    #[structopt(about = "Restore a snapshot. Without a name, restores the last known good configuration.")]
+    Restore {
+        name: Option<String>,
+    },
     Show,
     Paths,
+    #[structopt(about = "Save the current configuration as a named snapshot.")]
+    Save {
+        name: String,
+        #[structopt(short, long, about = "Overwrite snapshot if necessary.")]
+        force: bool,
+    },
+    #[structopt(about = "List saved snapshots.")]
+    List,
+    #[structopt(about = "Keep the current configuration as the last known good snapshot, once SigGen starts with it.")]
+    MarkGood,
+    #[structopt(about = "Write a named profile to the path SigGen reads.")]
+    Use {
+        profile: String,
+    },
+    #[structopt(about = "List saved profiles. The active one is marked with '*'.")]
+    Profiles,
+    #[structopt(about = "Save the current configuration as a named profile.")]
+    SaveProfile {
+        name: String,
+        #[structopt(short, long, about = "Added as the profile's first comment line.")]
+        description: Option<String>,
+        #[structopt(short, long, about = "Overwrite profile if necessary.")]
+        force: bool,
+    },
+    #[structopt(about = "Check the configuration for syntax errors and summarize its modules.")]
+    Check,
+    #[structopt(about = "Print a value. Keys are 'key' or 'module.key', e.g. 'M9484C@2.options'.")]
+    Get {
+        key: String,
+    },
+    #[structopt(about = "Set a value, preserving comments and formatting elsewhere in the file.")]
+    Set {
+        key: String,
+        value: String,
+    },
+    #[structopt(
+        about = "Compare two configurations. Each side is a path, 'snapshot:<name>', a report or '<report>.zip!<file>'."
+    )]
+    Diff {
+        a: String,
+        b: String,
+        #[structopt(short, long, default_value = "auto", about = "auto, semantic or lines.")]
+        mode: Mode,
+    },
 }
 
 pub fn run(command: Command) -> anyhow::Result<()> {
     match command {
         Command::HwConfig(cmd) => match cmd {
-            HwConfigCommand::Restore => println!("Not yet implemented!"),
+            HwConfigCommand::Restore { name } => {
+                let path = hwconfig::restore_snapshot(&name.unwrap_or_else(|| hwconfig::LAST_KNOWN_GOOD.to_string()))?;
+                println!("{}", path.display());
+            }
             HwConfigCommand::Show => match hwconfig::read_from(&hwconfig::get_path_or_cwd()) {
                 Some(text) => {
                     println!("{}", text)
@@ -69,54 +352,487 @@ pub fn run(command: Command) -> anyhow::Result<()> {
                     println!("No hwconfig found")
                 }
             },
-            HwConfigCommand::Paths => {
-                for path in hwconfig::valid_paths() {
-                    println!("{} {}", path.display(), path.exists())
+            HwConfigCommand::Paths => print_resolution(&config_resolution::resolve(ConfigFile::HwConfig)),
+            HwConfigCommand::Save { name, force } => {
+                if !force && hwconfig::read_snapshot(&name).is_some() {
+                    return Err(anyhow::anyhow!(
+                        "Snapshot already exists: {}\n\
+                         Consider using the --force flag or using a unique name.",
+                        name
+                    ));
+                }
+                let text = hwconfig::read_from(&hwconfig::get_path_or_cwd())
+                    .ok_or_else(|| anyhow::anyhow!("No hwconfig found"))?;
+                hwconfig::save_snapshot(&name, &text)?;
+            }
+            HwConfigCommand::MarkGood => {
+                let path = hwconfig::mark_known_good()?;
+                println!("{}", path.display());
+            }
+            HwConfigCommand::List => {
+                for snapshot in hwconfig::list_snapshots() {
+                    println!("{:30} {}", snapshot.name, snapshot.modified)
+                }
+            }
+            HwConfigCommand::Use { profile } => {
+                let profile = hwconfig_profiles::get_profile(&profile)
+                    .ok_or_else(|| anyhow::anyhow!("No profile named {}", profile))?;
+                let path = hwconfig::get_path_or_cwd();
+                if let Some(warning) = hwconfig::set_text(&path, &profile.text)? {
+                    eprintln!("{}", warning);
+                }
+                println!("{}", path.display());
+            }
+            HwConfigCommand::Profiles => {
+                let profiles = hwconfig_profiles::list_profiles();
+                let current = hwconfig::read_from(&hwconfig::get_path_or_cwd()).unwrap_or_default();
+                let active = hwconfig_profiles::matching_profile(&profiles, &current).map(|x| x.name.clone());
+                for profile in &profiles {
+                    println!(
+                        "{} {:30} {}{}",
+                        if active.as_ref() == Some(&profile.name) { "*" } else { " " },
+                        profile.name,
+                        profile.description,
+                        if profile.builtin { " (built-in)" } else { "" }
+                    );
+                }
+                println!("\nUser profiles directory: {}", hwconfig_profiles::profiles_dir().display());
+            }
+            HwConfigCommand::SaveProfile {
+                name,
+                description,
+                force,
+            } => {
+                if !force && hwconfig_profiles::get_profile(&name).is_some() {
+                    return Err(anyhow::anyhow!(
+                        "Profile already exists: {}\n\
+                         Consider using the --force flag or using a unique name.",
+                        name
+                    ));
+                }
+                let text = hwconfig::read_from(&hwconfig::get_path_or_cwd())
+                    .ok_or_else(|| anyhow::anyhow!("No hwconfig found"))?;
+                let path = hwconfig_profiles::save_profile(&name, description.as_deref(), &text)?;
+                println!("{}", path.display());
+            }
+            HwConfigCommand::Check => {
+                let path = hwconfig::get_path_or_cwd();
+                let text = hwconfig::read_from(&path).ok_or_else(|| anyhow::anyhow!("No hwconfig found"))?;
+                let config = hwconfig_parser::parse(&text)?.model();
+                println!("{}", path.display());
+                for module in config.modules {
+                    println!(
+                        "{:20} slot {:5} options: {}",
+                        module.name,
+                        module.slot.map(|x| x.to_string()).unwrap_or_else(|| "-".to_string()),
+                        module.options.join(", ")
+                    );
+                }
+            }
+            HwConfigCommand::Get { key } => {
+                let text = hwconfig::read_from(&hwconfig::get_path_or_cwd())
+                    .ok_or_else(|| anyhow::anyhow!("No hwconfig found"))?;
+                match hwconfig_parser::parse(&text)?.get(&key)? {
+                    Some(value) => println!("{}", value),
+                    None => return Err(anyhow::anyhow!("Key not found: {}", key)),
+                }
+            }
+            HwConfigCommand::Set { key, value } => {
+                let path = hwconfig::get_path_or_cwd();
+                let mut document = hwconfig_parser::parse(&hwconfig::read_from(&path).unwrap_or_default())?;
+                document.set(&key, &value)?;
+                if let Some(warning) = hwconfig::set_text(&path, &document.to_string())? {
+                    eprintln!("{}", warning);
+                }
+                println!("{}", path.display());
+            }
+            HwConfigCommand::Diff { a, b, mode } => {
+                let diff = hwconfig_diff::diff(&hwconfig_diff::read_source(&a)?, &hwconfig_diff::read_source(&b)?, mode);
+                if mode == Mode::Semantic && diff.mode != Mode::Semantic {
+                    println!("Syntax errors, comparing lines instead");
+                }
+                if diff.changes.is_empty() {
+                    println!("No differences");
+                }
+                for change in diff.changes {
+                    println!("{}", change);
                 }
             }
         },
+        Command::Ion(cmd) => match cmd {
+            IonCommand::Show => {
+                let path = ion_diagnostics::get_config_path_or_cwd();
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&ion_diagnostics::get_config_from(&path))?
+                );
+            }
+            IonCommand::Path => {
+                let path = ion_diagnostics::get_config_path_or_cwd();
+                println!("{} {}", path.display(), path.exists());
+            }
+            IonCommand::Harvest { logs } => {
+                let catalog = Catalog::refresh(&logs);
+                println!(
+                    "{} setting paths, {} operation names",
+                    catalog.setting_paths.len(),
+                    catalog.operation_names.len()
+                );
+            }
+            IonCommand::Complete { kind, prefix } => {
+                let catalog = Catalog::load();
+                for entry in catalog.suggest(kind, &prefix.unwrap_or_default(), usize::MAX) {
+                    println!("{}", entry);
+                }
+            }
+            IonCommand::Validate => {
+                let config = ion_diagnostics::get_config_from(&ion_diagnostics::get_config_path_or_cwd())
+                    .ok_or_else(|| anyhow::anyhow!("No Ion Diagnostics configuration found"))?;
+                let catalog = Catalog::load();
+                let settings: Vec<String> =
+                    config.settings.instance.into_iter().flat_map(|x| x.setting_paths).collect();
+                let operations: Vec<String> = config.operations.instance.into_iter().flat_map(|x| x.names).collect();
+                let settings_known = print_unknown(&catalog, CatalogKind::Settings, &settings);
+                let operations_known = print_unknown(&catalog, CatalogKind::Operations, &operations);
+                if !settings_known || !operations_known {
+                    return Err(anyhow::anyhow!("Configuration references unknown entries"));
+                }
+            }
+            IonCommand::AddSetting { paths, force } => {
+                if !force && !print_unknown(&Catalog::load(), CatalogKind::Settings, &paths) {
+                    return Err(anyhow::anyhow!("Consider using the --force flag to add unknown entries."));
+                }
+                let path = ion_diagnostics::get_config_path_or_cwd();
+                let mut config = ion_diagnostics::get_config_from(&path).unwrap_or_default();
+                config.settings.instance.push(SettingsInstance {
+                    setting_paths: paths,
+                    ..Default::default()
+                });
+                ion_diagnostics::set_config(&path, config)?;
+                println!("{}", path.display());
+            }
+            IonCommand::AddOperation { names, force } => {
+                if !force && !print_unknown(&Catalog::load(), CatalogKind::Operations, &names) {
+                    return Err(anyhow::anyhow!("Consider using the --force flag to add unknown entries."));
+                }
+                let path = ion_diagnostics::get_config_path_or_cwd();
+                let mut config = ion_diagnostics::get_config_from(&path).unwrap_or_default();
+                config.operations.instance.push(OperationsInstance {
+                    names,
+                    ..Default::default()
+                });
+                ion_diagnostics::set_config(&path, config)?;
+                println!("{}", path.display());
+            }
+        },
         Command::Log(cmd) => match cmd {
             LogCommand::Show { .. } => {
                 logging::show()?;
             }
-            LogCommand::Paths => {
-                for path in logging::valid_paths() {
-                    println!("{} {}", path.display(), path.exists())
-                }
-            }
+            LogCommand::Paths => print_resolution(&config_resolution::resolve(ConfigFile::Logging)),
             LogCommand::SinkPath => {
                 println!("{}", logging::get_log_path_from_current_config().display());
             }
         },
         Command::Report(cmd) => match cmd {
-            ReportCommand::Zip { name, force } => {
-                let file_name = report::zip_file_name(&name);
-                if !force && Path::new(&file_name).exists() {
-                    return Err(anyhow::anyhow!(
-                        "Destination file already exists: {}\n\
-                         Consider using the --force flag or using a unique name.",
-                        file_name
-                    ));
+            ReportCommand::Zip {
+                name,
+                force,
+                output,
+                passphrase,
+                profile,
+            } => {
+                create_report(&name, output.as_deref(), force, passphrase, profile)?;
+            }
+            ReportCommand::Decrypt { file, identity, output } => {
+                let output = output.unwrap_or_else(|| {
+                    let name = file.to_string_lossy();
+                    report::unique_file_path(PathBuf::from(match name.strip_suffix(ENCRYPTED_EXTENSION) {
+                        Some(stem) => stem.to_string(),
+                        None => format!("{}.decrypted", name),
+                    }))
+                });
+                report_encryption::decrypt_file(&file, &output, &identity, || report_encryption::read_passphrase(false))?;
+                println!("{}", output.display());
+            }
+            ReportCommand::Collectors { profile } => {
+                let profile = profile.resolve()?;
+                for collector in Collector::iter() {
+                    let status = if profile.enabled(collector) { "enabled" } else { "excluded" };
+                    println!("{:24} {}", collector.to_string(), status);
+                }
+                for pattern in &profile.extra {
+                    println!("extra pattern: {}", pattern);
+                }
+                for path in profile.extra_paths() {
+                    println!("extra file: {}", path.display());
+                }
+                for path in logging::get_log_file_paths(profile.log_days()) {
+                    println!("log file: {}", path.display());
+                }
+                println!("max log size: {}", report_profile::format_size(profile.max_log_size()));
+                println!("redact: {}", profile.redact);
+                for rule in &profile.redaction_rules {
+                    println!("redaction rule {}: {}", rule.name, rule.pattern);
+                }
+                if let Some(size) = profile.max_file_size {
+                    println!("max file size: {}", report_profile::format_size(size));
+                }
+                if let Some(size) = profile.max_total_size {
+                    println!("max total size: {}", report_profile::format_size(size));
                 }
-                println!("{}", file_name);
-                report::create_report(&name)?;
             }
-            ReportCommand::Upload { name } => {
-                let file_name = report::zip_file_name(&name);
+            ReportCommand::Upload {
+                report,
+                create,
+                ticket,
+                reporter,
+                passphrase,
+                profile,
+            } => {
+                let path = if create {
+                    create_report(&report, None, false, passphrase, profile)?
+                } else {
+                    find_report(&report)?
+                };
+                let properties = UploadProperties::for_report(&path, reporter, ticket);
                 let client = versions::VersionsClient::default();
-                let handle = client.upload_report(Path::new(&file_name), None, None)?;
-                if handle.join().is_err() {
-                    return Err(anyhow::anyhow!("unknown error occurred when uploading"));
+                let status = Arc::new(Mutex::new(UploadStatus::Idle));
+                let handle = client.upload_report(&path, &properties, status.clone(), None)?;
+                while !handle.is_finished() {
+                    print!("\r{:79}", progress_line(&status.lock().unwrap()));
+                    std::io::stdout().flush()?;
+                    std::thread::sleep(Duration::from_millis(200));
                 }
-                println!(
-                    "{}/{}/{}",
-                    versions::BASE_FILE_URL,
-                    versions::report_segments(),
-                    file_name
-                );
+                println!("\r{:79}", progress_line(&status.lock().unwrap()));
+                match handle.join() {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(err)) => return Err(anyhow::anyhow!("Upload failed: {}", err)),
+                    Err(_) => return Err(anyhow::anyhow!("unknown error occurred when uploading")),
+                }
+                println!("{}", client.report_url(&path));
+                print!("{}", properties);
+            }
+            ReportCommand::Remote(RemoteCommand::List { host, since }) => {
+                let reports = versions::VersionsClient::default().list_reports(host.as_deref(), since)?;
+                for report in reports.iter() {
+                    print!("{}", report.name);
+                    for (name, value) in report.properties.iter() {
+                        print!("  {}={}", name, value);
+                    }
+                    println!();
+                }
+            }
+            ReportCommand::Remote(RemoteCommand::Get { name, output }) => {
+                let path = match output {
+                    Some(dir) if dir.is_dir() => report::unique_file_path(dir.join(&name)),
+                    Some(path) => path,
+                    None => report::unique_file_path(in_cwd(&name)),
+                };
+                versions::VersionsClient::default().download_report(&name, &path)?;
+                println!("{}", path.display());
+            }
+            ReportCommand::Inspect { zip } => print_report_info(&report_archive::inspect(&zip)?),
+            ReportCommand::Extract { zip, dir } => {
+                let dir = dir.unwrap_or_else(|| report_archive::default_extract_dir(&zip));
+                let written = report_archive::extract(&zip, &dir)?;
+                println!("Extracted {} files to {}", written.len(), dir.display());
+            }
+            ReportCommand::Apply {
+                zip,
+                only,
+                dry_run,
+                force,
+            } => {
+                let applied = report_archive::apply(&zip, &only, dry_run, force)?;
+                if applied.is_empty() {
+                    println!("Nothing to apply");
+                }
+                for applied in applied {
+                    println!("{}", applied);
+                }
+                if dry_run {
+                    println!("Dry run, nothing was changed");
+                }
+            }
+            ReportCommand::Compare { a, b, json } => {
+                let comparison = report_compare::compare(&a, &b)?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&comparison)?);
+                } else {
+                    print!("{}", comparison);
+                }
+            }
+            ReportCommand::Watch {
+                interval,
+                pattern,
+                cooldown,
+                keep,
+                upload,
+                profile,
+            } => {
+                let options = report_watch::WatchOptions {
+                    interval: std::time::Duration::from_secs(interval.max(1)),
+                    patterns: if pattern.is_empty() { vec!["[critical]".to_string()] } else { pattern },
+                    cooldown: std::time::Duration::from_secs(cooldown * 60),
+                    keep,
+                    upload,
+                };
+                report_watch::watch(&options, &profile.resolve()?)?;
+            }
+            ReportCommand::Cat { zip, file } => {
+                std::io::stdout().write_all(&report_archive::read_file(&zip, &file)?)?;
             }
         },
+        Command::Remotes => {
+            let (selected, remote) = remotes::selected();
+            for (name, configured) in remotes::remotes(&remotes::load_settings()?) {
+                // The selected one shows any URL override.
+                let url = if name == selected { &remote.url } else { &configured.url };
+                println!("{} {:15} {}", if name == selected { "*" } else { " " }, name, url);
+            }
+            println!("\nSettings: {}", remotes::settings_path().display());
+        }
+        Command::Auth(cmd) => {
+            let (remote, remotes::Remote { url, .. }) = remotes::selected();
+            match cmd {
+                AuthCommand::Login { user, api_key, store } => {
+                    let credential = match (user, api_key) {
+                        (Some(user), _) => Credential::Basic {
+                            password: credentials::read_secret(&format!("Password for {}: ", user))?,
+                            user,
+                        },
+                        (None, true) => Credential::ApiKey {
+                            key: credentials::read_secret("API key: ")?,
+                        },
+                        (None, false) => Credential::Token {
+                            token: credentials::read_secret("Access token: ")?,
+                        },
+                    };
+                    let store = store.unwrap_or_default();
+                    credentials::save(&remote, &url, &credential, store)?;
+                    println!("Saved {} for {} at {} in the {}", credential, remote, url, store);
+                    print_access(&versions::VersionsClient::default());
+                }
+                AuthCommand::Logout => match credentials::delete(&remote, &url)? {
+                    true => println!("Removed the credential for {} at {}", remote, url),
+                    false => println!("No credential saved for {} at {}", remote, url),
+                },
+                AuthCommand::Status => {
+                    match credentials::load(&remote, &url)? {
+                        Some((credential, store)) => println!("{} at {}: {}, in the {}", remote, url, credential, store),
+                        None => println!("{} at {}: anonymous", remote, url),
+                    }
+                    print_access(&versions::VersionsClient::default());
+                }
+            }
+        }
         _ => {return Err(anyhow::anyhow!("unrecognized command"))}
     };
     Ok(())
 }
+
+/// Whether the remote lets the saved credential, or no credential, list packages.
+fn print_access(client: &versions::VersionsClient) {
+    match client.get_packages_branch_names() {
+        Ok(branches) => println!("{}: {} package branches", client.remote.url, branches.len()),
+        Err(err) => println!("{}", err),
+    }
+}
+
+/// A progress bar while uploading, otherwise the status.
+fn progress_line(status: &UploadStatus) -> String {
+    match status {
+        UploadStatus::Uploading { sent, total, .. } => {
+            const WIDTH: u64 = 30;
+            let done = (sent * WIDTH).checked_div(*total).unwrap_or(WIDTH) as usize;
+            format!("[{}{}] {}", "#".repeat(done), "-".repeat(WIDTH as usize - done), status)
+        }
+        // The error is returned and printed on its own.
+        UploadStatus::Error(_) => String::new(),
+        status => status.to_string(),
+    }
+}
+
+/// Prints the path before creating the report, since collecting can take a while.
+fn create_report(
+    name: &str,
+    output: Option<&Path>,
+    force: bool,
+    passphrase: bool,
+    profile: ProfileOptions,
+) -> anyhow::Result<PathBuf> {
+    let profile = profile.resolve()?;
+    let passphrase = passphrase.then(|| report_encryption::read_passphrase(true)).transpose()?;
+    let encryption = report_encryption::for_report(&profile, passphrase)?;
+    let mut path = report::output_path(name, profile.format, output);
+    if encryption.is_some() && output != Some(path.as_path()) {
+        path = report_encryption::encrypted_path(&path);
+    }
+    if !force {
+        path = report::unique_file_path(path);
+    }
+    if let Some(dir) = path.parent().filter(|x| !x.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    println!("{}", path.display());
+    report::create_report(&path, name, &profile, encryption.as_ref())?;
+    Ok(path)
+}
+
+/// `report` if it is a file, otherwise today's report with that name in any format, encrypted or not.
+fn find_report(report: &str) -> anyhow::Result<PathBuf> {
+    if Path::new(report).is_file() {
+        return Ok(PathBuf::from(report));
+    }
+    ArchiveFormat::iter()
+        .map(|format| report::report_file_name(report, format))
+        .flat_map(|name| [report_encryption::encrypted_path(Path::new(&name)), PathBuf::from(name)])
+        .find(|path| path.is_file())
+        .ok_or_else(|| anyhow::anyhow!("No report file {} or report named {} created today", report, report))
+}
+
+fn print_report_info(info: &ReportInfo) {
+    match &info.manifest {
+        Some(manifest) => {
+            println!("Report:    {}", manifest.report_name);
+            println!("Generated: {}", manifest.generated);
+            println!("Toolkit:   {}", manifest.toolkit_version);
+            println!("SigGen:    {}", manifest.installed_version.as_deref().unwrap_or("not installed"));
+            println!("Host:      {}", manifest.host_name.as_deref().unwrap_or("unknown"));
+        }
+        None => println!("No manifest, the report was created by an older toolkit."),
+    }
+    if let Some(summary) = &info.summary {
+        println!("\n{}", summary.trim_end());
+    }
+    println!("\nFiles:");
+    for entry in &info.entries {
+        println!("{:>10}  {}", format_bytes(entry.size), entry.name);
+    }
+    for skipped in info.manifest.iter().flat_map(|manifest| &manifest.skipped) {
+        println!("{:>10}  skipped: {} ({})", format_bytes(skipped.size), skipped.original_path.display(), skipped.reason);
+    }
+}
+
+fn print_unknown(catalog: &Catalog, kind: CatalogKind, entries: &[String]) -> bool {
+    let mut all_known = true;
+    for entry in entries {
+        if catalog.validate(kind, entry) == Validation::Unknown {
+            all_known = false;
+            println!("Unknown {}: {}", kind, entry);
+            let suggestions = catalog.suggest(kind, entry, 5);
+            if !suggestions.is_empty() {
+                println!("    Did you mean: {}", suggestions.join(", "));
+            }
+        }
+    }
+    all_known
+}
+
+/// One line per location SigGen checks, in search order.
+fn print_resolution(resolution: &Resolution) {
+    for candidate in &resolution.candidates {
+        println!("{:8} {}", candidate.status.to_string(), candidate.path.display());
+    }
+}
@@ -5,6 +5,23 @@ pub fn in_cwd<P: AsRef<Path>>(file: P) -> PathBuf {
     std::env::current_dir().unwrap().join(file)
 }
 
+/// Where the toolkit keeps its own state, independent of any SigGen installation. Falls back to the temporary
+/// directory for accounts without a home directory, such as some services.
+pub fn toolkit_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("SigGenToolkit")
+}
+
+/// Names the toolkit saves files under, like snapshots and profiles, can't leave their directory or be hidden.
+pub fn check_name(kind: &str, name: &str) -> anyhow::Result<()> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\', ':']) {
+        return Err(anyhow::anyhow!("Invalid {} name: {}", kind, name));
+    }
+    Ok(())
+}
+
 pub fn open_explorer(path: &Path) -> anyhow::Result<()> {
     #[cfg(not(target_arch = "wasm32"))]
     process::Command::new(if cfg!(windows) { "explorer" } else { "xdg-open" })
@@ -13,3 +30,29 @@ pub fn open_explorer(path: &Path) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Two decimal places in the largest fitting binary unit, e.g. `15.54 GiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.2} {}", value, units[unit]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::format_bytes;
+
+    #[test]
+    fn formatting() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(16_686_137_344), "15.54 GiB");
+    }
+}
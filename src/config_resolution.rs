@@ -0,0 +1,144 @@
+use crate::common::*;
+use crate::{hwconfig, logging};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use strum::{Display, EnumIter, EnumString};
+
+#[derive(Debug, Copy, Clone, PartialEq, EnumIter, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum ConfigFile {
+    Logging,
+    HwConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Default, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Status {
+    /// The file SigGen reads.
+    Active,
+    /// Exists, but a file earlier in the search order wins.
+    Shadowed,
+    #[default]
+    Missing,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub path: PathBuf,
+    pub status: Status,
+}
+
+/// Every location SigGen checks for a file, in the order it checks them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Resolution {
+    pub candidates: Vec<Candidate>,
+}
+
+impl ConfigFile {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ConfigFile::Logging => logging::FILE_NAME,
+            ConfigFile::HwConfig => hwconfig::FILE_NAME,
+        }
+    }
+
+    /// The working directory first, then the install locations.
+    pub fn search_order(&self) -> Vec<PathBuf> {
+        let installed = siggen_dirs().into_iter().map(|dir| dir.join(self.file_name()));
+        let mut order: Vec<PathBuf> = std::iter::once(in_cwd(self.file_name())).chain(installed).collect();
+        let mut seen = vec![];
+        order.retain(|path| {
+            let duplicate = seen.contains(path);
+            seen.push(path.clone());
+            !duplicate
+        });
+        order
+    }
+}
+
+impl Resolution {
+    pub fn active(&self) -> Option<&Candidate> {
+        self.candidates.iter().find(|candidate| candidate.status == Status::Active)
+    }
+
+    pub fn shadowed(&self) -> Vec<&Candidate> {
+        self.candidates
+            .iter()
+            .filter(|candidate| candidate.status == Status::Shadowed)
+            .collect()
+    }
+
+    pub fn status_of(&self, path: &std::path::Path) -> Status {
+        self.candidates
+            .iter()
+            .find(|candidate| candidate.path == path)
+            .map_or(Status::Missing, |candidate| candidate.status)
+    }
+}
+
+pub fn resolve(file: ConfigFile) -> Resolution {
+    resolve_paths(file.search_order())
+}
+
+/// The first existing file is active, later ones are shadowed by it.
+fn resolve_paths(order: Vec<PathBuf>) -> Resolution {
+    let mut found = false;
+    let candidates = order
+        .into_iter()
+        .map(|path| {
+            let status = match path.is_file() {
+                false => Status::Missing,
+                true if found => Status::Shadowed,
+                true => {
+                    found = true;
+                    Status::Active
+                }
+            };
+            Candidate { path, status }
+        })
+        .collect();
+    Resolution { candidates }
+}
+
+/// Directories SigGen installs look in, excluding the working directory.
+pub fn siggen_dirs() -> Vec<PathBuf> {
+    if cfg!(windows) {
+        vec![dirs::document_dir(), Some(PathBuf::from("E:\\"))]
+            .into_iter()
+            .flatten()
+            .map(|x| x.join("Keysight").join("PathWave").join("SignalGenerator"))
+            .collect()
+    } else {
+        dirs::home_dir().into_iter().map(|x| x.join("userdata")).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::in_cwd;
+    use crate::config_resolution::{resolve_paths, siggen_dirs, ConfigFile, Status};
+
+    #[test]
+    fn search_order() {
+        let hwconfig = ConfigFile::HwConfig.search_order();
+        assert_eq!(hwconfig[0], in_cwd("sghal_dev.cfg"));
+        assert_eq!(hwconfig.len(), siggen_dirs().len() + 1);
+        let logging = ConfigFile::Logging.search_order();
+        assert_eq!(logging[0], in_cwd("ksflogger.cfg"));
+        assert_eq!(logging.len(), siggen_dirs().len() + 1);
+    }
+
+    #[test]
+    fn shadowing() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let order = vec![dir.join("a.cfg"), dir.join("b.cfg"), dir.join("c.cfg")];
+        std::fs::write(&order[1], "").unwrap();
+        std::fs::write(&order[2], "").unwrap();
+        let resolution = resolve_paths(order.clone());
+        let statuses: Vec<Status> = resolution.candidates.iter().map(|x| x.status).collect();
+        assert_eq!(statuses, [Status::Missing, Status::Active, Status::Shadowed]);
+        assert_eq!(resolution.active().unwrap().path, order[1]);
+        assert!(resolve_paths(vec![dir.join("missing.cfg")]).active().is_none());
+    }
+}
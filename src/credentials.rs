@@ -0,0 +1,208 @@
+use crate::common::toolkit_dir;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use strum::{Display, EnumString};
+
+/// How requests to an Artifactory remote authenticate.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Credential {
+    ApiKey { key: String },
+    /// An access token, sent as a bearer token.
+    Token { token: String },
+    Basic { user: String, password: String },
+}
+
+impl Credential {
+    /// The headers sent with every request.
+    pub fn headers(&self) -> anyhow::Result<HeaderMap> {
+        use base64::Engine;
+        let (name, value) = match self {
+            Credential::ApiKey { key } => (API_KEY_HEADER.parse()?, key.clone()),
+            Credential::Token { token } => (AUTHORIZATION, format!("Bearer {}", token)),
+            Credential::Basic { user, password } => (
+                AUTHORIZATION,
+                format!(
+                    "Basic {}",
+                    base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, password))
+                ),
+            ),
+        };
+        let mut value = HeaderValue::from_str(&value)?;
+        value.set_sensitive(true);
+        Ok(HeaderMap::from_iter([(name, value)]))
+    }
+}
+
+/// Leaves out the secret.
+impl fmt::Debug for Credential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+/// Leaves out the secret.
+impl fmt::Display for Credential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Credential::ApiKey { .. } => write!(f, "API key"),
+            Credential::Token { .. } => write!(f, "access token"),
+            Credential::Basic { user, .. } => write!(f, "user {}", user),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Store {
+    /// Windows Credential Manager, the macOS keychain or the Linux kernel keyring. The Linux one only lasts until
+    /// the user logs out.
+    Keyring,
+    /// A JSON file only the user can read.
+    File,
+}
+
+impl Default for Store {
+    /// The file on Linux, where the kernel keyring doesn't outlive the login session.
+    fn default() -> Self {
+        if cfg!(target_os = "linux") {
+            Store::File
+        } else {
+            Store::Keyring
+        }
+    }
+}
+
+/// Credentials saved in the file, by [`account`].
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+struct CredentialsFile {
+    remotes: BTreeMap<String, Credential>,
+}
+
+/// Credentials belong to a remote name and URL together, so a URL given with `--artifactory-url` or
+/// `SGT_ARTIFACTORY_URL` never gets the secret saved for the configured one.
+fn account(remote: &str, url: &str) -> String {
+    format!("{}@{}", remote, url)
+}
+
+/// Reads the secret from `SGT_ARTIFACTORY_SECRET`, or asks for it on the terminal.
+pub fn read_secret(prompt: &str) -> anyhow::Result<String> {
+    if let Ok(secret) = std::env::var(SECRET_VARIABLE) {
+        return Ok(secret);
+    }
+    let secret = rpassword::prompt_password(prompt)?;
+    if secret.is_empty() {
+        return Err(anyhow::anyhow!("Nothing entered"));
+    }
+    Ok(secret)
+}
+
+pub fn credentials_path() -> PathBuf {
+    toolkit_dir().join(CREDENTIALS_FILE_NAME)
+}
+
+/// The credential for the remote at `url` and where it came from, looking in the keyring first.
+pub fn load(remote: &str, url: &str) -> anyhow::Result<Option<(Credential, Store)>> {
+    let account = account(remote, url);
+    // A keyring that can't be reached is as good as an empty one, since the file may have the credential.
+    if let Ok(text) = keyring_entry(&account).and_then(|entry| Ok(entry.get_password()?)) {
+        return Ok(Some((serde_json::from_str(&text)?, Store::Keyring)));
+    }
+    Ok(read_file()?.remotes.remove(&account).map(|x| (x, Store::File)))
+}
+
+/// Replaces any credential the remote at `url` had in either store.
+pub fn save(remote: &str, url: &str, credential: &Credential, store: Store) -> anyhow::Result<()> {
+    delete(remote, url)?;
+    let account = account(remote, url);
+    match store {
+        Store::Keyring => keyring_entry(&account)?
+            .set_password(&serde_json::to_string(credential)?)
+            .map_err(|err| anyhow::anyhow!("Unable to save to the keyring, consider --store file: {}", err)),
+        Store::File => {
+            let mut file = read_file()?;
+            file.remotes.insert(account, credential.clone());
+            write_file(&file)
+        }
+    }
+}
+
+/// Removes the credential for the remote at `url` from both stores. Returns whether there was one.
+pub fn delete(remote: &str, url: &str) -> anyhow::Result<bool> {
+    let account = account(remote, url);
+    let in_keyring = match keyring_entry(&account).map(|entry| entry.delete_credential()) {
+        Ok(Ok(_)) => true,
+        Ok(Err(keyring::Error::NoEntry)) | Err(_) => false,
+        Ok(Err(err)) => return Err(err.into()),
+    };
+    let mut file = read_file()?;
+    let in_file = file.remotes.remove(&account).is_some();
+    if in_file {
+        write_file(&file)?;
+    }
+    Ok(in_keyring || in_file)
+}
+
+fn keyring_entry(account: &str) -> anyhow::Result<keyring::Entry> {
+    Ok(keyring::Entry::new(KEYRING_SERVICE, account)?)
+}
+
+/// Refuses a file others can read, like ssh does with private keys.
+fn read_file() -> anyhow::Result<CredentialsFile> {
+    let path = credentials_path();
+    if !path.exists() {
+        return Ok(CredentialsFile::default());
+    }
+    check_permissions(&path)?;
+    let text = std::fs::read_to_string(&path)?;
+    serde_json::from_str(&text).map_err(|err| anyhow::anyhow!("{}: {}", path.display(), err))
+}
+
+fn write_file(file: &CredentialsFile) -> anyhow::Result<()> {
+    let path = credentials_path();
+    std::fs::create_dir_all(path.parent().unwrap())?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode only applies to new files.
+        if path.exists() {
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    let mut writer = options.open(&path)?;
+    writer.write_all(serde_json::to_string_pretty(file)?.as_bytes())?;
+    Ok(())
+}
+
+/// On Windows the file is in the user's profile, which other users can't read.
+#[cfg(unix)]
+fn check_permissions(path: &Path) -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(path)?.permissions().mode();
+    if mode & 0o077 != 0 {
+        return Err(anyhow::anyhow!(
+            "{} can be read by other users, restrict it with: chmod 600 {}",
+            path.display(),
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_path: &Path) -> anyhow::Result<()> {
+    Ok(())
+}
+
+const SECRET_VARIABLE: &str = "SGT_ARTIFACTORY_SECRET";
+const API_KEY_HEADER: &str = "X-JFrog-Art-Api";
+const KEYRING_SERVICE: &str = "sgt-artifactory";
+const CREDENTIALS_FILE_NAME: &str = "credentials.json";
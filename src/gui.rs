@@ -1,16 +1,22 @@
-use crate::gui_state::{FilterOptions, HwconfigState, IonDiagnosticsState, LoggingState, PathInfo, ReportsState, VersionsFilter, VersionsState, VersionsTypes};
-use crate::logging::{Bool, Level, Logger, Sink, Template};
-use crate::model::Model;
-use crate::versions::{FileInfo, RequestStatus, BASE_FILE_URL};
-use crate::{common, hwconfig, ion_diagnostics, logging, report, versions};
+use crate::config_resolution::{ConfigFile, Status};
+use crate::report_profile::{self, Collector, ReportProfile};
+use crate::report_format::ArchiveFormat;
+use crate::report_upload::UploadProperties;
+use crate::gui_state::{FilterOptions, HwconfigState, IonDiagnosticsState, LoggingState, PathInfo, Pending, ReportsState, VersionsFilter, VersionsState, VersionsTypes};
+use crate::logging::{Bool, Level, Logger, LoggingConfiguration, Sink, Template};
+use crate::model::{Model, NativeModel};
+use crate::versions::{FileInfo, RequestStatus, UploadStatus};
+use crate::hwconfig_diff::{ChangeKind, Diff};
+use crate::{common, hwconfig, hwconfig_diff, hwconfig_parser, hwconfig_profiles, ion_diagnostics, logging, report};
 #[cfg(not(target_arch = "arm"))]
 use clipboard::ClipboardProvider;
 use eframe::egui::Visuals;
 use eframe::{egui, egui::Ui, epi};
 use std::collections::BTreeMap;
-use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use strum::{Display, EnumIter, IntoEnumIterator};
+use crate::ion_catalog::{Catalog, CatalogKind, Validation};
 use crate::ion_diagnostics::{OperationsInstance, SettingsInstance};
 
 enum SinksAction {
@@ -35,7 +41,7 @@ enum Tabs {
 }
 
 struct GuiApp {
-    model: Box<dyn Model>,
+    model: Arc<dyn Model>,
     selected_tab: Option<Tabs>,
     hwconfig: HwconfigState,
     logger: LoggingState,
@@ -106,7 +112,7 @@ impl epi::App for GuiApp {
                     self.report(ui, frame);
                 }
                 Some(Tabs::IonDiagnostics) => {
-                    self.diagnostics(ui);
+                    self.diagnostics(ui, frame);
                 }
                 // Some(Tabs::LogViewer) => {
                 //     self.log_viewer(ui);
@@ -125,36 +131,40 @@ impl epi::App for GuiApp {
         let logging_config_path = self.model.logging_get_config_path().unwrap_or_else(|| self.in_cwd(logging::FILE_NAME));
         self.logger.config = self.model.logging_get_config_from(&logging_config_path).unwrap_or_default();
         self.logger.loaded_from = Some(logging_config_path);
-        let logger_cwd_path = self.in_cwd(logging::FILE_NAME);
-        self.logger.cwd_path_info = PathInfo {
-            path: logger_cwd_path.clone(),
-            file_exists: self.model.file_exists(&logger_cwd_path)
-        };
         for path in self.model.logging_valid_paths().iter() {
-            self.logger.valid_paths_info.push(PathInfo { path: path.clone(), file_exists: self.model.file_exists(path) });
+            self.logger.valid_paths_info.push(PathInfo { path: path.clone(), ..Default::default() });
         }
+        self.update_logging_paths_info();
 
-        let ion_debug_dir = std::env::var(ion_diagnostics::ENV_VAR).ok().map(|x| PathBuf::from(x).join(ion_diagnostics::FILE_NAME));
+        let ion_debug_dir = self.model.ion_diagnostics_get_debug_dir().map(|x| x.join(ion_diagnostics::FILE_NAME));
         let path = match ion_debug_dir.clone() {
-            Some(x) if x.exists() => { x }
+            Some(x) if self.model.file_exists(&x) => { x }
             _ => { self.in_cwd(ion_diagnostics::FILE_NAME) }
         };
         self.diagnostics.ion_debug_dir_info = ion_debug_dir.map(|_| PathInfo {
             path: path.clone(),
-            file_exists: self.model.file_exists(&path)
+            file_exists: self.model.file_exists(&path),
+            ..Default::default()
         });
-        self.diagnostics.config = ion_diagnostics::get_config_from(&path).unwrap_or_default();
+        self.diagnostics.config = self.model.ion_diagnostics_get_config_from(&path).unwrap_or_default();
         self.diagnostics.loaded_from = Some(path);
+        if self.diagnostics.ion_debug_dir_info.is_some() {
+            self.refresh_catalog(_frame);
+        }
 
-        let hwconfig_cwd_path = self.in_cwd(hwconfig::FILE_NAME);
-        self.hwconfig.cwd_path_info = PathInfo {
-            path: hwconfig_cwd_path.clone(),
-            file_exists: self.model.file_exists(&hwconfig_cwd_path)
-        };
-        for path in hwconfig::valid_paths().iter() {
-            self.hwconfig.valid_paths_info.push(PathInfo { path: path.clone(), file_exists: self.model.file_exists(path) });
+        for path in self.model.hwconfig_valid_paths().iter() {
+            self.hwconfig.valid_paths_info.push(PathInfo { path: path.clone(), ..Default::default() });
         }
+        self.update_hwconfig_paths_info();
+        if let Some(path) = self.hwconfig.active_path.clone() {
+            self.hwconfig.text = self.model.hwconfig_read_from(&path).unwrap_or_default();
+            self.hwconfig.loaded_from = Some(path);
+        }
+        self.hwconfig.snapshots = self.model.hwconfig_list_snapshots();
+        self.hwconfig.profiles = self.model.hwconfig_list_profiles();
+        self.update_active_profile();
 
+        self.set_report_profile(self.model.report_get_profile());
         self.update_report_summary();
 
         // let stdin_data = self.log_viewer.stdin_data.clone();
@@ -170,7 +180,7 @@ impl epi::App for GuiApp {
 impl GuiApp {
     fn new(model: Box<dyn Model>) -> Self {
         Self {
-            model: model,
+            model: Arc::from(model),
             hwconfig: Default::default(),
             logger: Default::default(),
             // log_viewer: Default::default(),
@@ -201,46 +211,334 @@ impl GuiApp {
             ui,
             "Name",
             &mut self.reports.name,
-            Some("Descriptive name for report .zip file. Required."),
+            Some("Descriptive name for report file. Required."),
         );
+        ui.horizontal(|ui| {
+            ui.label("Format");
+            egui::ComboBox::from_id_source("report_format_dropdown")
+                .selected_text(self.reports.profile.format.to_string())
+                .show_ui(ui, |ui| {
+                    for format in ArchiveFormat::iter() {
+                        ui.selectable_value(&mut self.reports.profile.format, format, format.to_string());
+                    }
+                });
+        });
 
-        if self.reports.name_changed() {
-            self.reports.zip_file_path = self.in_cwd(self.model.report_zip_file_name(&self.reports.name));
+        if self.reports.file_name_changed() {
+            self.reports.zip_file_path = self.in_cwd(self.model.report_file_name(&self.reports.name, self.reports.profile.format));
             self.reports.generate_status = None;
             self.reports.file_exists = self.model.file_exists(&self.reports.zip_file_path);
-            *self.reports.upload_status.lock().unwrap() = RequestStatus::Idle;
+            *self.reports.upload_status.lock().unwrap() = UploadStatus::Idle;
         }
 
         ui.add_enabled_ui(!self.reports.name.is_empty(), |ui| {
             copyable_path(ui, &self.reports.zip_file_path);
         });
 
-        self.report_generate_button(ui, &self.reports.zip_file_path.clone());
+        self.report_generate_button(ui);
 
         ui.separator();
         ui.heading("Upload to Artifactory");
         ui.hyperlink_to(
             "Upload Location",
-            format!("{}/{}", BASE_FILE_URL, versions::report_segments()),
+            format!("{}/{}", self.packages.client.remote.url, self.packages.client.remote.reports),
         );
+        text_edit_labeled(ui, "Ticket", &mut self.reports.ticket, Some("Optional, attached to the upload."));
         self.report_upload_button(ui, frame, &self.reports.zip_file_path.clone());
 
         ui.separator();
         self.report_summary(ui);
+
+        ui.separator();
+        self.stored_reports(ui);
+
+        ui.separator();
+        self.remote_reports(ui, frame);
+
+        ui.separator();
+        self.open_report(ui);
     }
 
-    fn report_generate_button(&mut self, ui: &mut Ui, path: &Path) {
+    fn stored_reports(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.heading("Stored Reports")
+                .on_hover_text("Reports in the working directory of the backend");
+            if ui.button("Refresh").clicked() {
+                self.refresh_stored_reports();
+            }
+        });
+        let mut refresh = false;
+        match self.reports.stored_reports.clone() {
+            Some(Ok(reports)) if reports.is_empty() => {
+                ui.label("No reports found");
+            }
+            Some(Ok(reports)) => {
+                egui::ScrollArea::vertical()
+                    .id_source("stored_reports")
+                    .max_height(150.0)
+                    .show(ui, |ui| {
+                        for report in reports.iter() {
+                            ui.horizontal(|ui| {
+                                if ui.button("Open").clicked() {
+                                    self.reports.open_path = self.in_cwd(&report.name).to_string_lossy().to_string();
+                                    self.reports.open_local = false;
+                                    self.inspect_open_path();
+                                }
+                                if ui.button("⬇").on_hover_text("Download to this machine").clicked() {
+                                    self.reports.stored_status = Some(
+                                        self.model
+                                            .report_fetch_stored(&report.name)
+                                            .map_err(|err| format!("{}: {}", report.name, err)),
+                                    );
+                                }
+                                if ui.button("🗑").on_hover_text("Delete").clicked() {
+                                    self.reports.stored_status = self
+                                        .model
+                                        .report_delete_stored(&report.name)
+                                        .err()
+                                        .map(|err| Err(format!("{}: {}", report.name, err)));
+                                    refresh = true;
+                                }
+                                ui.monospace(&report.name);
+                                ui.label(common::format_bytes(report.size));
+                            });
+                        }
+                    });
+            }
+            Some(Err(err)) => error_label(ui, &err),
+            None => {}
+        }
+        if refresh {
+            self.refresh_stored_reports();
+        }
+        match &self.reports.stored_status {
+            Some(Ok(path)) => copyable_path(ui, path),
+            Some(Err(err)) => error_label(ui, err),
+            None => {}
+        }
+    }
+
+    fn refresh_stored_reports(&mut self) {
+        self.reports.stored_reports = Some(self.model.report_list_stored().map_err(|err| err.to_string()));
+    }
+
+    /// Listing and downloading talk to Artifactory, so both run in the background.
+    fn remote_reports(&mut self, ui: &mut Ui, frame: &epi::Frame) {
+        if let Some(listed) = take_pending(&mut self.reports.listing_remote_reports) {
+            self.reports.remote_reports = Some(listed);
+        }
+        if let Some(downloaded) = take_pending(&mut self.reports.downloading_report) {
+            match downloaded {
+                Ok(path) => {
+                    self.reports.remote_error = None;
+                    self.reports.open_path = path.to_string_lossy().to_string();
+                    self.reports.open_local = true;
+                    self.inspect_open_path();
+                }
+                Err(err) => self.reports.remote_error = Some(err),
+            }
+        }
+
+        ui.heading("Remote Reports");
+        ui.horizontal(|ui| {
+            ui.label("Host");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.reports.remote_host)
+                    .hint_text("Any")
+                    .desired_width(120.0),
+            );
+            if self.reports.listing_remote_reports.is_some() {
+                ui.add(egui::Spinner::new());
+            } else if ui.button("Refresh").clicked() {
+                let host = Some(self.reports.remote_host.trim().to_string()).filter(|x| !x.is_empty());
+                let client = self.packages.client.clone();
+                self.reports.listing_remote_reports = Some(spawn_pending(frame, move || {
+                    client.list_reports(host.as_deref(), None).map_err(|err| err.to_string())
+                }));
+            }
+        });
+        let downloading = self.reports.downloading_report.is_some();
+        match self.reports.remote_reports.clone() {
+            Some(Ok(reports)) if reports.is_empty() => {
+                ui.label("No reports found");
+            }
+            Some(Ok(reports)) => {
+                egui::ScrollArea::vertical()
+                    .id_source("remote_reports")
+                    .max_height(150.0)
+                    .show(ui, |ui| {
+                        for report in reports.iter() {
+                            ui.horizontal(|ui| {
+                                let download = ui
+                                    .add_enabled(!downloading, egui::Button::new("⬇"))
+                                    .on_hover_text("Download to this machine and open");
+                                if download.clicked() {
+                                    self.download_report(frame, &report.name);
+                                }
+                                ui.monospace(&report.name);
+                            });
+                        }
+                    });
+            }
+            Some(Err(err)) => error_label(ui, &err),
+            None => {}
+        }
+        if downloading {
+            ui.add(egui::Spinner::new());
+        }
+        if let Some(err) = &self.reports.remote_error {
+            error_label(ui, err);
+        }
+    }
+
+    /// Downloads to the working directory of this machine, which isn't the backend's in frontend mode, to be
+    /// opened below once it's done.
+    fn download_report(&mut self, frame: &epi::Frame, name: &str) {
+        let client = self.packages.client.clone();
+        let name = name.to_string();
+        self.reports.downloading_report = Some(spawn_pending(frame, move || {
+            let path = report::unique_file_path(common::in_cwd(&name));
+            client
+                .download_report(&name, &path)
+                .map(|_| path)
+                .map_err(|err| format!("{}: {}", name, err))
+        }));
+    }
+
+    /// Reads the open report where it is: downloaded reports are on this machine, others on the backend.
+    fn report_model(&self) -> &dyn Model {
+        if self.reports.open_local {
+            &NativeModel
+        } else {
+            self.model.as_ref()
+        }
+    }
+
+    fn inspect_open_path(&mut self) {
+        let path = PathBuf::from(self.reports.open_path.trim());
+        self.reports.open_info = Some(self.report_model().report_inspect(&path).map_err(|err| err.to_string()));
+        self.reports.viewer = None;
+        self.reports.viewer_error = None;
+    }
+
+    fn open_report(&mut self, ui: &mut Ui) {
+        ui.heading("Open Report");
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.reports.open_path).hint_text("Path to a report"));
+            if ui.button("Open").clicked() {
+                self.reports.open_local = false;
+                self.inspect_open_path();
+            }
+        });
+        let info = match self.reports.open_info.clone() {
+            Some(Ok(info)) => info,
+            Some(Err(err)) => return error_label(ui, &err),
+            None => return,
+        };
+        match &info.manifest {
+            Some(manifest) => {
+                ui.monospace(format!("Generated: {}", manifest.generated));
+                ui.monospace(format!("Toolkit Version: {}", manifest.toolkit_version));
+                ui.monospace(format!(
+                    "SigGen Version: {}",
+                    manifest.installed_version.as_deref().unwrap_or("Not Found")
+                ));
+            }
+            None => warning_label(ui, "No manifest, the report was created by an older toolkit"),
+        }
+        if let Some(summary) = &info.summary {
+            ui.collapsing("Summary", |ui| ui.monospace(summary));
+        }
+        let log = info.files_of(Collector::Log).into_iter().next();
+        let log_config = info.files_of(Collector::LogConfig).into_iter().next();
+        ui.horizontal(|ui| {
+            if ui.add_enabled(log.is_some(), egui::Button::new("View Log")).clicked() {
+                self.view_report_file(log.as_ref().unwrap());
+            }
+            if ui
+                .add_enabled(log_config.is_some(), egui::Button::new("Open Log Config in Editor"))
+                .clicked()
+            {
+                self.open_report_log_config(log_config.as_ref().unwrap());
+            }
+        });
+        ui.collapsing(format!("Files ({})", info.entries.len()), |ui| {
+            for entry in info.entries.iter() {
+                ui.horizontal(|ui| {
+                    if ui.small_button("View").clicked() {
+                        self.view_report_file(&entry.name);
+                    }
+                    ui.monospace(format!("{:>10}  {}", common::format_bytes(entry.size), entry.name));
+                });
+            }
+        });
+        if let Some(err) = &self.reports.viewer_error {
+            error_label(ui, err);
+        }
+        if let Some((name, lines)) = &self.reports.viewer {
+            ui.horizontal(|ui| {
+                ui.strong(name);
+                ui.add(egui::TextEdit::singleline(&mut self.reports.viewer_filter).hint_text("Filter"));
+            });
+            let filter = self.reports.viewer_filter.to_lowercase();
+            let lines: Vec<&String> = lines.iter().filter(|x| x.to_lowercase().contains(&filter)).collect();
+            let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+            egui::ScrollArea::both()
+                .id_source("report_viewer")
+                .max_height(400.0)
+                .show_rows(ui, row_height, lines.len(), |ui, range| {
+                    for line in &lines[range] {
+                        ui.monospace(line.as_str());
+                    }
+                });
+        }
+    }
+
+    fn view_report_file(&mut self, name: &str) {
+        let zip = PathBuf::from(self.reports.open_path.trim());
+        match self.report_model().report_read_text(&zip, name) {
+            Ok(text) => {
+                self.reports.viewer = Some((name.to_string(), text.lines().map(|x| x.to_string()).collect()));
+                self.reports.viewer_error = None;
+            }
+            Err(err) => self.reports.viewer_error = Some(err.to_string()),
+        }
+    }
+
+    fn open_report_log_config(&mut self, name: &str) {
+        let zip = PathBuf::from(self.reports.open_path.trim());
+        let config = self
+            .report_model()
+            .report_read_text(&zip, name)
+            .and_then(|text| Ok(serde_json::from_str::<LoggingConfiguration>(&text)?));
+        match config {
+            Ok(config) => {
+                self.logger.config = config;
+                self.logger.loaded_from = None;
+                self.logger.read_only = Some(format!("{} from {}", name, zip.display()));
+                self.selected_tab = Some(Tabs::LoggingConfiguration);
+                self.reports.viewer_error = None;
+            }
+            Err(err) => self.reports.viewer_error = Some(format!("{}: {}", name, err)),
+        }
+    }
+
+    fn report_generate_button(&mut self, ui: &mut Ui) {
         ui.horizontal(|ui| {
             ui.add_enabled_ui(
                 !self.reports.name.is_empty() && self.reports.generate_status != Some(true),
                 |ui| {
                     if ui.button("Generate Report").clicked() {
-                        self.reports.generate_status = match self.model.report_create_report(&self.reports.name) {
-                            Ok(_) => Some(true),
+                        self.reports.generate_status = match self.model.report_create_report(&self.reports.name, &self.reports.profile) {
+                            Ok(created) => {
+                                // Encrypted reports get another extension.
+                                self.reports.zip_file_path = created;
+                                Some(true)
+                            }
                             Err(_) => Some(false),
                         };
-                        self.reports.file_exists = self.model.file_exists(path);
-                        *self.reports.upload_status.lock().unwrap() = RequestStatus::Idle;
+                        self.reports.file_exists = self.model.file_exists(&self.reports.zip_file_path);
+                        *self.reports.upload_status.lock().unwrap() = UploadStatus::Idle;
                     }
                 },
             );
@@ -257,36 +555,43 @@ impl GuiApp {
         });
     }
 
+    /// Uploads read the report and send progress on this machine, so they are only offered when the backend runs
+    /// here too.
     fn report_upload_button(&mut self, ui: &mut Ui, frame: &epi::Frame, path: &Path) {
+        if !self.model.is_local() {
+            ui.label("Upload from the backend's machine with 'sgt report upload'.");
+            return;
+        }
         ui.add_enabled_ui(self.reports.file_exists, |ui| {
-            ui.horizontal(|ui| match *self.reports.upload_status.lock().unwrap() {
-                RequestStatus::InProgress => {
-                    ui.strong("Uploading...");
+            let status = self.reports.upload_status.lock().unwrap().clone();
+            ui.horizontal(|ui| match status {
+                UploadStatus::Hashing | UploadStatus::Verifying => {
+                    ui.strong(status.to_string());
                 }
-                RequestStatus::Error => {
+                UploadStatus::Uploading { sent, total, .. } => {
+                    let progress = if total == 0 { 1.0 } else { sent as f32 / total as f32 };
+                    ui.add(egui::ProgressBar::new(progress).text(status.to_string()));
+                }
+                UploadStatus::Retrying { .. } => warning_label(ui, &status.to_string()),
+                UploadStatus::Error(_) => {
                     if ui.button("⬆  Retry ").clicked() {
                         self.upload_clicked(frame, path);
                     }
-                    error_label(ui, "Upload failed");
+                    error_label(ui, &status.to_string());
                 }
-                RequestStatus::Idle => {
+                UploadStatus::Idle => {
                     if ui.button("⬆  Upload").clicked() {
                         self.upload_clicked(frame, path);
                     }
                 }
-                RequestStatus::Success => {
+                UploadStatus::Success => {
                     ui.add_enabled_ui(false, |ui| {
                         if ui.button("⬆  Upload").clicked() {
                             // Do Nothing
                         }
                     });
                     ui.strong("Upload complete");
-                    let url = format!(
-                        "{}/{}/{}",
-                        BASE_FILE_URL,
-                        versions::report_segments(),
-                        path.file_name().unwrap().to_string_lossy()
-                    );
+                    let url = self.packages.client.report_url(path);
 
                     #[cfg(not(target_arch = "arm"))]
                     if ui.button("🗐").on_hover_text(&url).clicked() {
@@ -300,18 +605,13 @@ impl GuiApp {
     }
 
     fn upload_clicked(&self, frame: &epi::Frame, path: &Path) {
-        // TODO: backend
-        if self
-            .packages
-            .client
-            .upload_report(
-                path,
-                Some(self.reports.upload_status.clone()),
-                Some(frame.clone()),
-            )
-            .is_err()
-        {
-            *self.reports.upload_status.lock().unwrap() = RequestStatus::Error;
+        if let Err(err) = self.packages.client.upload_report(
+            path,
+            &UploadProperties::for_report(path, None, Some(self.reports.ticket.clone())),
+            self.reports.upload_status.clone(),
+            Some(frame.clone()),
+        ) {
+            *self.reports.upload_status.lock().unwrap() = UploadStatus::Error(err.to_string());
         }
     }
 
@@ -323,112 +623,432 @@ impl GuiApp {
             }
         });
 
-        ui.monospace(format!(
-            "Installed Version: {}",
-            match &self.reports.installed_version {
-                None => "Not Found",
-                Some(version) => version,
-            }
-        ));
-        ui.monospace(format!(
-            "Log File Path: {}",
-            match &self.reports.log_file_path {
-                None => "Not Found".to_string(),
-                Some(path) => path.display().to_string(),
-            }
-        ));
-        ui.monospace(format!(
-            "Exception Log File Path: {}",
-            match &self.reports.exception_log_file_path {
-                None => "Not Found".to_string(),
-                Some(path) => path.display().to_string(),
-            }
-        ));
-        ui.monospace(format!(
-            "Log Config Path: {}",
-            match &self.reports.log_cfg_path {
-                None => "Not Found".to_string(),
-                Some(path) => path.display().to_string(),
+        ui.label("Unchecked items are left out of the report.");
+        let profile = &mut self.reports.profile;
+        collector_line(
+            ui,
+            profile,
+            Collector::Version,
+            format!(
+                "Installed Version: {}",
+                match &self.reports.installed_version {
+                    None => "Not Found",
+                    Some(version) => version,
+                }
+            ),
+        );
+        collector_line(
+            ui,
+            profile,
+            Collector::HostName,
+            format!(
+                "Host Name: {}",
+                match &self.reports.host_name {
+                    None => "Not Found",
+                    Some(host_name) => host_name,
+                }
+            ),
+        );
+        collector_line(
+            ui,
+            profile,
+            Collector::SystemInfo,
+            "System Information: OS, CPU, memory, disk space, SigGen processes".to_string(),
+        );
+        collector_line(
+            ui,
+            profile,
+            Collector::Log,
+            format!(
+                "Log File Paths: {}",
+                if self.reports.log_file_paths.is_empty() {
+                    "Not Found".to_string()
+                } else {
+                    self.reports
+                        .log_file_paths
+                        .iter()
+                        .map(|x| x.display().to_string())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                }
+            ),
+        );
+        collector_line(
+            ui,
+            profile,
+            Collector::ExceptionLog,
+            format!(
+                "Exception Log File Path: {}",
+                path_or_not_found(&self.reports.exception_log_file_path)
+            ),
+        );
+        collector_line(
+            ui,
+            profile,
+            Collector::LogConfig,
+            format!("Log Config Path: {}", path_or_not_found(&self.reports.log_cfg_path)),
+        );
+        collector_line(
+            ui,
+            profile,
+            Collector::NoResetSystemSettings,
+            format!(
+                "No Reset System Settings Path: {}",
+                path_or_not_found(&self.reports.no_reset_system_settings_path)
+            ),
+        );
+        collector_line(
+            ui,
+            profile,
+            Collector::StateFiles,
+            format!(
+                "Data Directory State Files: {}",
+                if self.reports.data_dir_state_files.is_empty() {
+                    "Not Found".to_string()
+                } else {
+                    self.reports.data_dir_state_files.join(", ")
+                }
+            ),
+        );
+        collector_line(
+            ui,
+            profile,
+            Collector::HwConfig,
+            format!("HW Config Path: {}", path_or_not_found(&self.reports.hwconfig_path)),
+        );
+        collector_line(ui, profile, Collector::Extra, "Extra Files (one glob pattern per line):".to_string());
+        if ui
+            .add(egui::TextEdit::multiline(&mut self.reports.extra_text).desired_rows(2))
+            .changed()
+        {
+            self.reports.profile.extra = self
+                .reports
+                .extra_text
+                .lines()
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect();
+        }
+        ui.horizontal(|ui| {
+            ui.label("Max File Size:");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.reports.max_file_size_text)
+                    .hint_text("No limit")
+                    .desired_width(80.0),
+            );
+            let size = match self.reports.max_file_size_text.trim() {
+                "" => Ok(None),
+                text => report_profile::parse_size(text).map(Some),
+            };
+            match size {
+                Ok(size) if response.changed() => self.reports.profile.max_file_size = size,
+                Err(_) => error_label(ui, "Expected a size such as 512K or 10M"),
+                _ => {}
             }
-        ));
-        ui.monospace(format!(
-            "No Reset System Settings Path: {}",
-            match &self.reports.no_reset_system_settings_path {
-                None => "Not Found".to_string(),
-                Some(path) => path.display().to_string(),
+        });
+        ui.checkbox(&mut self.reports.profile.redact, "Redact host names, user names, addresses and emails")
+            .on_hover_text(format!(
+                "{} custom rules in the saved report profile",
+                self.reports.profile.redaction_rules.len()
+            ));
+        ui.label("Encrypt To (age public keys or files of keys, one per line):");
+        if ui
+            .add(egui::TextEdit::multiline(&mut self.reports.encrypt_to_text).desired_rows(1))
+            .changed()
+        {
+            self.reports.profile.encrypt_to = self
+                .reports
+                .encrypt_to_text
+                .lines()
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect();
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Save as Default").clicked() {
+                self.reports.profile_error = self.model.report_set_profile(&self.reports.profile).is_err();
             }
-        ));
-        ui.monospace(format!(
-            "Data Directory State Files: {}",
-            if self.reports.data_dir_state_files.is_empty() {
-                "Not Found".to_string()
-            } else {
-                self.reports.data_dir_state_files.join(", ")
+            if ui.button("Revert").clicked() {
+                self.set_report_profile(self.model.report_get_profile());
             }
-        ));
-        ui.monospace(format!(
-            "HW Config Path: {}",
-            match &self.reports.hwconfig_path {
-                None => "Not Found".to_string(),
-                Some(path) => path.display().to_string(),
+            if self.reports.profile_error {
+                error_label(ui, "Error saving report profile");
             }
-        ));
+        });
+    }
+
+    fn set_report_profile(&mut self, profile: ReportProfile) {
+        self.reports.extra_text = profile.extra.join("\n");
+        self.reports.encrypt_to_text = profile.encrypt_to.join("\n");
+        self.reports.max_file_size_text = profile.max_file_size.map(report_profile::format_size).unwrap_or_default();
+        self.reports.profile = profile;
+        self.reports.profile_error = false;
     }
 
     fn update_report_summary(&mut self) {
-        let path = self.model.logging_get_log_path_from_current_config();
-        self.reports.log_file_path = if path.exists() { Some(path) } else { None };
+        self.reports.log_file_paths = self.model.logging_get_log_file_paths(self.reports.profile.log_days());
 
         let path = self.model.get_exception_log_path();
-        self.reports.exception_log_file_path = if path.exists() { Some(path) } else { None };
+        self.reports.exception_log_file_path = if self.model.file_exists(&path) { Some(path) } else { None };
 
         self.reports.log_cfg_path = self.model.logging_get_config_path();
 
-        let path = report::get_no_reset_system_settings_path();
-        self.reports.no_reset_system_settings_path = if path.exists() { Some(path) } else { None };
+        let path = self.model.report_get_no_reset_system_settings_path();
+        self.reports.no_reset_system_settings_path = if self.model.file_exists(&path) { Some(path) } else { None };
 
         self.reports.data_dir_state_files = self.model.report_get_data_dir_state_file_paths();
 
         self.reports.hwconfig_path = self.model.hwconfig_get_path();
         self.reports.installed_version = self.model.installed_version();
+        self.reports.host_name = gethostname::gethostname().to_str().map(|x| x.to_string());
 
         self.reports.generate_status = None;
-        *self.reports.upload_status.lock().unwrap() = RequestStatus::Idle;
+        *self.reports.upload_status.lock().unwrap() = UploadStatus::Idle;
     }
 
     fn hwconfig(&mut self, ui: &mut Ui) {
-        ui.heading("Hardware Configuration");
+        ui.horizontal(|ui| {
+            ui.heading("Hardware Configuration");
+            ui.checkbox(&mut self.hwconfig.structured, "Structured Editor");
+        });
         ui.separator();
         ui.strong("Paths indexed by SigGen:");
         for path_info in self.hwconfig.valid_paths_info.clone().iter() {
             self.hwconfig_path(ui, &path_info);
         }
-        ui.strong("Current working directory:");
-        self.hwconfig_path(ui, &self.hwconfig.cwd_path_info.clone());
         ui.separator();
-        ui.add(egui::TextEdit::multiline(&mut self.hwconfig.text).hint_text("Enter desired hardware configuration and click Save above."));
+        self.hwconfig_profiles(ui);
+        ui.separator();
+        self.hwconfig_snapshots(ui);
+        ui.separator();
+        ui.collapsing("Compare", |ui| self.hwconfig_diff(ui));
+        ui.separator();
+        if self.hwconfig.structured {
+            hwconfig_structured(ui, &mut self.hwconfig);
+        } else {
+            ui.add(egui::TextEdit::multiline(&mut self.hwconfig.text).hint_text("Enter desired hardware configuration and click Save above."));
+        }
+    }
+
+    fn hwconfig_profiles(&mut self, ui: &mut Ui) {
+        ui.horizontal_wrapped(|ui| {
+            ui.strong("Profiles:");
+            for profile in self.hwconfig.profiles.clone() {
+                let active = self.hwconfig.active_profile.as_ref() == Some(&profile.name);
+                let hover = match profile.builtin {
+                    true => format!("{} (built-in)", profile.description),
+                    false => profile.description.clone(),
+                };
+                if ui
+                    .selectable_label(active, &profile.name)
+                    .on_hover_text(hover)
+                    .clicked()
+                {
+                    let path = self.hwconfig_active_path();
+                    self.hwconfig_write(&path, &profile.text);
+                    self.hwconfig.remove_error = false;
+                    if !self.hwconfig.write_error {
+                        self.hwconfig.text = profile.text.clone();
+                        self.hwconfig.loaded_from = Some(path);
+                    }
+                    self.update_hwconfig_paths_info();
+                    self.update_active_profile();
+                }
+            }
+            if !self.hwconfig.profiles.iter().any(|x| !x.builtin) {
+                ui.label("Save your own with 'sgt hwconfig save-profile <name>'");
+            }
+            if ui.button("⟳").on_hover_text("Refresh profiles").clicked() {
+                self.hwconfig.profiles = self.model.hwconfig_list_profiles();
+                self.update_active_profile();
+            }
+        });
+        match &self.hwconfig.active_profile {
+            Some(name) => ui.label(format!("Active file matches profile: {}", name)),
+            None => ui.label("Active file matches no profile"),
+        };
+    }
+
+    /// The file SigGen reads, or where it would look first if none exists yet.
+    fn hwconfig_active_path(&self) -> PathBuf {
+        self.hwconfig
+            .active_path
+            .clone()
+            .unwrap_or_else(|| self.in_cwd(hwconfig::FILE_NAME))
+    }
+
+    fn hwconfig_write(&mut self, path: &Path, text: &str) {
+        let written = self.model.hwconfig_set_text(path, text);
+        self.hwconfig.write_error = written.is_err();
+        self.hwconfig.write_warning = written.ok().flatten();
+    }
+
+    fn update_active_profile(&mut self) {
+        let text = self
+            .model
+            .hwconfig_read_from(&self.hwconfig_active_path())
+            .unwrap_or_default();
+        self.hwconfig.active_profile =
+            hwconfig_profiles::matching_profile(&self.hwconfig.profiles, &text).map(|x| x.name.clone());
+    }
+
+    fn update_hwconfig_paths_info(&mut self) {
+        let mut paths_info = std::mem::take(&mut self.hwconfig.valid_paths_info);
+        self.update_paths_info(ConfigFile::HwConfig, &mut paths_info);
+        self.hwconfig.valid_paths_info = paths_info;
+        self.hwconfig.active_path = self.model.hwconfig_get_path();
+    }
+
+    fn update_logging_paths_info(&mut self) {
+        let mut paths_info = std::mem::take(&mut self.logger.valid_paths_info);
+        self.update_paths_info(ConfigFile::Logging, &mut paths_info);
+        self.logger.valid_paths_info = paths_info;
+    }
+
+    fn update_paths_info(&self, file: ConfigFile, paths_info: &mut [PathInfo]) {
+        let resolution = self.model.config_resolve(file);
+        for path_info in paths_info.iter_mut() {
+            path_info.file_exists = self.model.file_exists(&path_info.path);
+            path_info.status = resolution.status_of(&path_info.path);
+        }
+    }
+
+    fn hwconfig_snapshots(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.strong("Snapshots:");
+            egui::ComboBox::from_id_source("hwconfig_snapshots_dropdown")
+                .selected_text("Load snapshot...")
+                .show_ui(ui, |ui| {
+                    for snapshot in self.hwconfig.snapshots.clone() {
+                        let text = format!("{}  ({})", snapshot.name, snapshot.modified);
+                        if ui.selectable_label(false, text).clicked() {
+                            self.hwconfig.text = self.model.hwconfig_read_snapshot(&snapshot.name).unwrap_or_default();
+                            self.hwconfig.loaded_from = None;
+                        }
+                    }
+                });
+            if ui.button("⟳").on_hover_text("Refresh snapshots").clicked() {
+                self.hwconfig.snapshots = self.model.hwconfig_list_snapshots();
+            }
+            if ui
+                .add_enabled(self.hwconfig.active_path.is_some(), egui::Button::new("Mark Known Good"))
+                .on_hover_text("Keep the configuration SigGen reads as the last known good snapshot")
+                .clicked()
+            {
+                self.hwconfig.snapshot_error = self.model.hwconfig_mark_known_good().is_err();
+                self.hwconfig.snapshots = self.model.hwconfig_list_snapshots();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.hwconfig.snapshot_name).hint_text("Snapshot name"));
+            let exists = self.hwconfig.snapshots.iter().any(|x| x.name == self.hwconfig.snapshot_name);
+            if ui
+                .add_enabled(!self.hwconfig.snapshot_name.is_empty(), egui::Button::new("Save Snapshot"))
+                .clicked()
+            {
+                self.hwconfig.snapshot_error = self
+                    .model
+                    .hwconfig_save_snapshot(&self.hwconfig.snapshot_name, &self.hwconfig.text)
+                    .is_err();
+                self.hwconfig.snapshots = self.model.hwconfig_list_snapshots();
+            }
+            if self.hwconfig.snapshot_error {
+                error_label(ui, "Error saving snapshot");
+            } else if exists {
+                warning_label(ui, "Snapshot already exists, will overwrite");
+            }
+        });
+    }
+
+    fn hwconfig_diff(&mut self, ui: &mut Ui) {
+        ui.label("Each side is a path, 'snapshot:<name>', a report or '<report>.zip!<file>'. Leave empty to use the editor.");
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.hwconfig.diff_left).hint_text("Editor"));
+            ui.label("⟷");
+            ui.add(egui::TextEdit::singleline(&mut self.hwconfig.diff_right).hint_text("Editor"));
+            egui::ComboBox::from_id_source("hwconfig_diff_mode")
+                .selected_text(self.hwconfig.diff_mode.to_string())
+                .show_ui(ui, |ui| {
+                    for mode in hwconfig_diff::Mode::iter() {
+                        ui.selectable_value(&mut self.hwconfig.diff_mode, mode, mode.to_string());
+                    }
+                });
+            if ui.button("Compare").clicked() {
+                self.hwconfig.diff = Some(self.compare_hwconfig());
+            }
+        });
+        match &self.hwconfig.diff {
+            Some(Ok(diff)) => {
+                ui.label(format!("{} differences ({})", diff.changes.len(), diff.mode));
+                for change in diff.changes.iter() {
+                    let color = match change.kind {
+                        ChangeKind::Added => egui::Color32::from_rgb(0, 255, 0),
+                        ChangeKind::Removed => egui::Color32::from_rgb(255, 0, 0),
+                        ChangeKind::Changed => egui::Color32::from_rgb(255, 255, 0),
+                    };
+                    ui.colored_label(color, egui::RichText::new(change.to_string()).monospace());
+                }
+            }
+            Some(Err(err)) => error_label(ui, err),
+            None => {}
+        }
+    }
+
+    fn compare_hwconfig(&self) -> Result<Diff, String> {
+        let read = |spec: &str| match spec.trim() {
+            "" => Ok(self.hwconfig.text.clone()),
+            spec => self.model.hwconfig_read_source(spec).map_err(|err| err.to_string()),
+        };
+        let left = read(&self.hwconfig.diff_left)?;
+        let right = read(&self.hwconfig.diff_right)?;
+        Ok(hwconfig_diff::diff(&left, &right, self.hwconfig.diff_mode))
     }
 
     fn hwconfig_path(&mut self, ui: &mut Ui, path_info: &PathInfo) {
         ui.horizontal(|ui| {
             copyable_path(ui, &path_info.path);
+            status_badge(ui, path_info.status);
             self.hwconfig_path_buttons(ui, path_info);
         });
     }
 
     fn hwconfig_path_buttons(&mut self, ui: &mut Ui, path_info: &PathInfo) {
+        ui.label(if self.hwconfig.loaded_from.as_ref() == Some(&path_info.path) {
+            "⬅"
+        } else {
+            "     "
+        });
+
+        if ui.add_enabled(path_info.file_exists, egui::Button::new("Load")).clicked() {
+            self.hwconfig.text = self.model.hwconfig_read_from(&path_info.path).unwrap_or_default();
+            self.hwconfig.loaded_from = Some(path_info.path.clone());
+        }
         if ui.button("Save").clicked() {
-            self.hwconfig.write_error = hwconfig::set_text(&path_info.path, &self.hwconfig.text).is_err();
+            self.hwconfig_write(&path_info.path, &self.hwconfig.text.clone());
             self.hwconfig.remove_error = false;
+            if !self.hwconfig.write_error {
+                self.hwconfig.loaded_from = Some(path_info.path.clone());
+            }
+            self.update_hwconfig_paths_info();
+            self.update_active_profile();
         }
         if ui.add_enabled(path_info.file_exists, egui::Button::new("Delete")).clicked() {
             self.hwconfig.write_error = false;
+            self.hwconfig.write_warning = None;
             self.hwconfig.remove_error = self.remove_file(&path_info.path).is_err();
+            if self.hwconfig.loaded_from == Some(path_info.path.clone()) {
+                self.hwconfig.loaded_from = None;
+            }
+            self.update_hwconfig_paths_info();
+            self.update_active_profile();
         }
 
         if self.hwconfig.write_error {
             error_label(ui, "Error writing configuration to file")
         }
+        if let Some(warning) = &self.hwconfig.write_warning {
+            warning_label(ui, warning)
+        }
         if self.hwconfig.remove_error {
             error_label(ui, "Error removing file")
         }
@@ -444,12 +1064,25 @@ impl GuiApp {
         for path in self.logger.valid_paths_info.clone().iter() {
             self.logging_path(ui, path);
         }
-        ui.collapsing("Other Paths", |ui| {
-            ui.strong("Current working directory:");
-            self.logging_path(ui, &self.logger.cwd_path_info.clone());
-        });
         ui.separator();
 
+        if let Some(source) = self.logger.read_only.clone() {
+            ui.horizontal(|ui| {
+                warning_label(ui, &format!("Read-only: {}", source));
+                if ui.button("Close").clicked() {
+                    let path = self.model.logging_get_config_path().unwrap_or_else(|| self.in_cwd(logging::FILE_NAME));
+                    self.logger.config = self.model.logging_get_config_from(&path).unwrap_or_default();
+                    self.logger.loaded_from = Some(path);
+                    self.logger.read_only = None;
+                }
+            });
+            ui.separator();
+        }
+
+        ui.add_enabled_ui(self.logger.read_only.is_none(), |ui| self.logging_editor(ui));
+    }
+
+    fn logging_editor(&mut self, ui: &mut Ui) {
         ui.horizontal_wrapped(|ui| {
             ui.strong("Templates:");
             for template in Template::iter() {
@@ -526,6 +1159,7 @@ impl GuiApp {
     fn logging_path(&mut self, ui: &mut Ui, path_info: &PathInfo) {
         ui.horizontal(|ui| {
             copyable_path(ui, &path_info.path);
+            status_badge(ui, path_info.status);
             self.logging_path_buttons(ui, path_info);
         });
     }
@@ -540,13 +1174,15 @@ impl GuiApp {
         if ui.add_enabled(path_info.file_exists, egui::Button::new("Load")).clicked() {
             self.logger.config = self.model.logging_get_config_from(&path_info.path).unwrap_or_default();
             self.logger.loaded_from = Some(path_info.path.clone());
+            self.logger.read_only = None;
         }
-        if ui.button("Save").clicked() {
+        if ui.add_enabled(self.logger.read_only.is_none(), egui::Button::new("Save")).clicked() {
             self.logger.remove_error = false;
             self.logger.write_error = self.model.logging_set_config(&path_info.path, self.logger.config.clone()).is_err();
             if !self.logger.write_error {
                 self.logger.loaded_from = Some(path_info.path.clone());
             }
+            self.update_logging_paths_info();
         }
         if ui.add_enabled(path_info.file_exists, egui::Button::new("Delete")).clicked() {
             self.logger.write_error = false;
@@ -554,6 +1190,7 @@ impl GuiApp {
             if self.logger.loaded_from == Some(path_info.path.clone()) {
                 self.logger.loaded_from = None;
             }
+            self.update_logging_paths_info();
         }
 
         if self.logger.write_error {
@@ -667,7 +1304,25 @@ impl GuiApp {
         action
     }
 
-    fn diagnostics(&mut self, ui: &mut Ui) {
+    /// Harvesting reads logs and state files, so it runs in the background and the catalogue is swapped in when
+    /// it's done.
+    fn refresh_catalog(&mut self, frame: &epi::Frame) {
+        let refreshed = Arc::new(Mutex::new(None));
+        self.diagnostics.refreshed_catalog = Some(refreshed.clone());
+        let model = self.model.clone();
+        let frame = frame.clone();
+        std::thread::spawn(move || {
+            *refreshed.lock().unwrap() = Some(model.ion_diagnostics_get_catalog());
+            frame.request_repaint();
+        });
+    }
+
+    fn diagnostics(&mut self, ui: &mut Ui, frame: &epi::Frame) {
+        let refreshed = self.diagnostics.refreshed_catalog.as_ref().and_then(|x| x.lock().unwrap().take());
+        if let Some(catalog) = refreshed {
+            self.diagnostics.catalog = catalog;
+            self.diagnostics.refreshed_catalog = None;
+        }
         ui.horizontal(|ui| {
             ui.heading("Ion Diagnostics");
             ui.hyperlink_to("Confluence Page", ion_diagnostics::CONFLUENCE_URL);
@@ -680,6 +1335,22 @@ impl GuiApp {
         }
         ui.strong("Paths indexed by Ion:");
         self.diagnostics_path(ui, &self.diagnostics.ion_debug_dir_info.clone().unwrap());
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "Catalogue: {} setting paths, {} operation names",
+                self.diagnostics.catalog.setting_paths.len(),
+                self.diagnostics.catalog.operation_names.len()
+            ));
+            if self.diagnostics.refreshed_catalog.is_some() {
+                ui.add(egui::Spinner::new());
+            } else if ui
+                .button("⟳  Refresh")
+                .on_hover_text("Scan state files and trace logs for names")
+                .clicked()
+            {
+                self.refresh_catalog(frame);
+            }
+        });
         ui.separator();
 
         ui.columns(2, |columns| {
@@ -741,7 +1412,7 @@ impl GuiApp {
                                     paths_to_remove.push(j);
                                 }
                             });
-
+                            catalog_hints(ui, path, &self.diagnostics.catalog, CatalogKind::Settings);
                         }
 
                         for index in paths_to_remove {
@@ -817,6 +1488,7 @@ impl GuiApp {
                                     paths_to_remove.push(j);
                                 }
                             });
+                            catalog_hints(ui, path, &self.diagnostics.catalog, CatalogKind::Operations);
                         }
 
                         ui.checkbox(&mut instance.flags.trace_all, "traceAll");
@@ -853,12 +1525,12 @@ impl GuiApp {
         });
 
         if ui.add_enabled(path_info.file_exists, egui::Button::new("Load")).clicked() {
-            self.diagnostics.config = ion_diagnostics::get_config_from(&path_info.path).unwrap_or_default();
+            self.diagnostics.config = self.model.ion_diagnostics_get_config_from(&path_info.path).unwrap_or_default();
             self.diagnostics.loaded_from = Some(path_info.path.clone());
         }
         if ui.button("Save").clicked() {
             self.diagnostics.remove_error = false;
-            self.diagnostics.write_error = ion_diagnostics::set_config(&path_info.path, self.diagnostics.config.clone()).is_err();
+            self.diagnostics.write_error = self.model.ion_diagnostics_set_config(&path_info.path, self.diagnostics.config.clone()).is_err();
             if !self.diagnostics.write_error {
                 self.diagnostics.loaded_from = Some(path_info.path.clone());
             }
@@ -922,8 +1594,8 @@ impl GuiApp {
         self.cwd.join(file)
     }
 
-    fn remove_file(&self, path: &Path) -> io::Result<()> {
-        std::fs::remove_file(path)
+    fn remove_file(&self, path: &Path) -> anyhow::Result<()> {
+        self.model.delete_file(path)
     }
 }
 
@@ -948,8 +1620,15 @@ fn versions(ui: &mut Ui, frame: &epi::Frame, state: &mut VersionsState, download
         if ui.button("⟳  Refresh").clicked() {
             state.refresh();
         }
+
+        ui.label(format!("Remote: {}", state.client.remote_name))
+            .on_hover_text(&state.client.remote.url);
     });
 
+    if let Some(err) = &state.error {
+        error_label(ui, err);
+    }
+
     ui.separator();
 
     ui.columns(2, |columns| {
@@ -1089,6 +1768,97 @@ fn download_clicked(frame: &epi::Frame, state: &mut VersionsState, file_info: &F
     }
 }
 
+/// Runs `task` on another thread, repainting when its result is ready for [`take_pending`].
+fn spawn_pending<T: Send + 'static>(frame: &epi::Frame, task: impl FnOnce() -> T + Send + 'static) -> Pending<T> {
+    let pending = Arc::new(Mutex::new(None));
+    let result = pending.clone();
+    let frame = frame.clone();
+    std::thread::spawn(move || {
+        *result.lock().unwrap() = Some(task());
+        frame.request_repaint();
+    });
+    pending
+}
+
+/// The result of a finished background task, clearing `pending` so another can be started.
+fn take_pending<T>(pending: &mut Option<Pending<T>>) -> Option<T> {
+    let result = pending.as_ref().and_then(|x| x.lock().unwrap().take());
+    if result.is_some() {
+        *pending = None;
+    }
+    result
+}
+
+fn hwconfig_structured(ui: &mut Ui, state: &mut HwconfigState) {
+    let parsed = match &mut state.parsed {
+        Some((text, parsed)) if *text == state.text => parsed,
+        cached => {
+            let parsed = hwconfig_parser::parse(&state.text).map(|document| {
+                let config = document.model();
+                (document, config)
+            });
+            &mut cached.insert((state.text.clone(), parsed)).1
+        }
+    };
+    let (document, config) = match parsed {
+        Ok((document, config)) => (document, config),
+        Err(errors) => {
+            for error in errors.0.iter() {
+                error_label(ui, &error.to_string());
+            }
+            ui.add(egui::TextEdit::multiline(&mut state.text));
+            return;
+        }
+    };
+
+    let mut changed = false;
+    let mut entries_grid = |ui: &mut Ui, id: usize, entries: &[hwconfig_parser::Entry]| {
+        egui::Grid::new(("hwconfig_entries", id)).striped(true).show(ui, |ui| {
+            for entry in entries {
+                ui.label(&entry.key).on_hover_text(format!("Line {}", entry.line));
+                let mut value = entry.value.clone();
+                if ui.text_edit_singleline(&mut value).changed() {
+                    document.set_line_value(entry.line, &value);
+                    changed = true;
+                }
+                ui.end_row();
+            }
+        });
+    };
+
+    egui::ScrollArea::vertical()
+        .id_source("hwconfig_structured_scroll")
+        .show(ui, |ui| {
+            if !config.globals.is_empty() {
+                ui.strong("Global");
+                entries_grid(ui, 0, &config.globals);
+            }
+            for module in &config.modules {
+                let header = match module.slot {
+                    Some(slot) => format!("{} (slot {})", module.name, slot),
+                    None => module.name.clone(),
+                };
+                egui::CollapsingHeader::new(header)
+                    .id_source(("hwconfig_module", module.line))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        if !module.options.is_empty() {
+                            ui.label(format!("Options: {}", module.options.join(", ")));
+                        }
+                        entries_grid(ui, module.line, &module.entries);
+                    });
+            }
+        });
+
+    if changed {
+        state.text = document.to_string();
+        *config = document.model();
+        if let Some((text, _)) = &mut state.parsed {
+            *text = state.text.clone();
+        }
+    }
+}
+
 fn text_edit_labeled(ui: &mut Ui, label: &str, content: &mut String, hint_text: Option<&str>) {
     ui.horizontal(|ui| {
         ui.label(label);
@@ -1108,6 +1878,37 @@ fn warning_label(ui: &mut Ui, label: &str) {
     ui.colored_label(egui::Color32::from_rgb(255, 255, 0), format!("⚠ {}", label));
 }
 
+fn collector_line(ui: &mut Ui, profile: &mut ReportProfile, collector: Collector, text: String) {
+    ui.horizontal(|ui| {
+        let mut enabled = profile.enabled(collector);
+        if ui.checkbox(&mut enabled, "").on_hover_text(collector.to_string()).changed() {
+            profile.set_enabled(collector, enabled);
+        }
+        ui.monospace(text);
+    });
+}
+
+fn path_or_not_found(path: &Option<PathBuf>) -> String {
+    match path {
+        None => "Not Found".to_string(),
+        Some(path) => path.display().to_string(),
+    }
+}
+
+fn status_badge(ui: &mut Ui, status: Status) {
+    match status {
+        Status::Active => {
+            ui.colored_label(egui::Color32::from_rgb(0, 255, 0), "active")
+                .on_hover_text("SigGen reads this file");
+        }
+        Status::Shadowed => {
+            ui.colored_label(egui::Color32::from_rgb(255, 255, 0), "⚠ shadowed")
+                .on_hover_text("SigGen ignores this file, one earlier in its search order wins");
+        }
+        Status::Missing => {}
+    }
+}
+
 fn copyable_path(ui: &mut Ui, path: &Path) {
     let label = ui
         .selectable_label(false, path.to_str().unwrap())
@@ -1125,6 +1926,20 @@ fn copyable_path(ui: &mut Ui, path: &Path) {
     }
 }
 
+fn catalog_hints(ui: &mut Ui, entry: &mut String, catalog: &Catalog, kind: CatalogKind) {
+    if catalog.validate(kind, entry) != Validation::Unknown {
+        return;
+    }
+    ui.horizontal_wrapped(|ui| {
+        warning_label(ui, "Not in catalogue");
+        for suggestion in catalog.suggest(kind, entry, 5) {
+            if ui.small_button(&suggestion).clicked() {
+                *entry = suggestion;
+            }
+        }
+    });
+}
+
 fn truncate_ui(ui: &mut Ui, truncate: &mut Option<Bool>) {
     let mut trunc = logging::is_true(truncate);
     ui.checkbox(&mut trunc, "Truncate");
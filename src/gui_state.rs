@@ -1,24 +1,56 @@
+use crate::config_resolution::Status;
+use crate::hwconfig::Snapshot;
+use crate::hwconfig_diff::{self, Diff};
+use crate::hwconfig_parser::{Document, HwConfig, SyntaxErrors};
+use crate::hwconfig_profiles::Profile;
 use crate::logging::LoggingConfiguration;
-use crate::versions::{develop_branch, parse_semver, FileInfo, RequestStatus, SemVer, VersionsClient};
+use crate::report::StoredReport;
+use crate::report_archive::ReportInfo;
+use crate::report_format::ArchiveFormat;
+use crate::report_profile::ReportProfile;
+use crate::versions::{develop_branch, parse_semver, FileInfo, RemoteReport, RequestStatus, SemVer, UploadStatus, VersionsClient};
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use crate::ion_catalog::Catalog;
 use crate::ion_diagnostics::DiagnosticsConfiguration;
 
 #[derive(Default, Clone)]
 pub struct PathInfo {
     pub path: PathBuf,
-    pub file_exists: bool
+    pub file_exists: bool,
+    pub status: Status,
 }
 
+/// Filled in by a background thread when it's done.
+pub type Pending<T> = Arc<Mutex<Option<T>>>;
+
+pub type ParsedHwconfig = Result<(Document, HwConfig), SyntaxErrors>;
+
 #[derive(Default)]
 pub struct HwconfigState {
+    pub loaded_from: Option<PathBuf>,
+    pub structured: bool,
     pub write_error: bool,
+    /// Set when a save went through but the previous content couldn't be kept.
+    pub write_warning: Option<String>,
     pub remove_error: bool,
     pub text: String,
-    pub cwd_path_info: PathInfo,
-    pub valid_paths_info: Vec<PathInfo>
+    /// The file SigGen reads, refreshed along with the path infos.
+    pub active_path: Option<PathBuf>,
+    pub valid_paths_info: Vec<PathInfo>,
+    pub snapshots: Vec<Snapshot>,
+    pub snapshot_name: String,
+    pub snapshot_error: bool,
+    pub profiles: Vec<Profile>,
+    pub active_profile: Option<String>,
+    pub diff_left: String,
+    pub diff_right: String,
+    pub diff_mode: hwconfig_diff::Mode,
+    pub diff: Option<Result<Diff, String>>,
+    /// The structured editor's parse of `text`, redone only when the text changes.
+    pub parsed: Option<(String, ParsedHwconfig)>,
 }
 
 #[derive(Default)]
@@ -28,8 +60,9 @@ pub struct LoggingState {
     pub write_error: bool,
     pub remove_error: bool,
     pub advanced: bool,
-    pub cwd_path_info: PathInfo,
-    pub valid_paths_info: Vec<PathInfo>
+    pub valid_paths_info: Vec<PathInfo>,
+    /// Set while showing a configuration that didn't come from a path, e.g. one from a report zip.
+    pub read_only: Option<String>,
 }
 
 #[derive(Default)]
@@ -39,6 +72,9 @@ pub struct IonDiagnosticsState {
     pub write_error: bool,
     pub remove_error: bool,
     pub ion_debug_dir_info: Option<PathInfo>,
+    pub catalog: Catalog,
+    /// Filled in by the background refresh.
+    pub refreshed_catalog: Option<Arc<Mutex<Option<Catalog>>>>,
 }
 
 /// Recursive data structure. Intended to hold Major, Minor, and Patch versions as keys in nested maps
@@ -57,7 +93,8 @@ impl FilterOptions {
 pub struct ReportsState {
     pub name: String,
     pub previous_name: String,
-    pub log_file_path: Option<PathBuf>,
+    pub previous_format: ArchiveFormat,
+    pub log_file_paths: Vec<PathBuf>,
     pub exception_log_file_path: Option<PathBuf>,
     pub no_reset_system_settings_path: Option<PathBuf>,
     pub data_dir_state_files: Vec<String>,
@@ -66,15 +103,43 @@ pub struct ReportsState {
     pub installed_version: Option<String>,
     pub generate_status: Option<bool>,
     pub file_exists: bool,
-    pub upload_status: Arc<Mutex<RequestStatus>>,
+    pub upload_status: Arc<Mutex<UploadStatus>>,
     pub host_name: Option<String>,
-    pub zip_file_path: PathBuf
+    pub zip_file_path: PathBuf,
+    pub profile: ReportProfile,
+    pub extra_text: String,
+    pub encrypt_to_text: String,
+    /// Attached to uploads.
+    pub ticket: String,
+    pub max_file_size_text: String,
+    pub profile_error: bool,
+    pub remote_host: String,
+    pub remote_reports: Option<Result<Vec<RemoteReport>, String>>,
+    /// Set while the remote reports are listed in the background.
+    pub listing_remote_reports: Option<Pending<Result<Vec<RemoteReport>, String>>>,
+    /// Set while a remote report is downloaded in the background.
+    pub downloading_report: Option<Pending<Result<PathBuf, String>>>,
+    pub remote_error: Option<String>,
+    /// Reports in the backend's working directory.
+    pub stored_reports: Option<Result<Vec<StoredReport>, String>>,
+    /// Where the last stored report was downloaded to.
+    pub stored_status: Option<Result<PathBuf, String>>,
+    pub open_path: String,
+    /// Whether the open report is on this machine rather than the backend's.
+    pub open_local: bool,
+    pub open_info: Option<Result<ReportInfo, String>>,
+    /// Archive path and lines of the file shown in the report viewer.
+    pub viewer: Option<(String, Vec<String>)>,
+    pub viewer_filter: String,
+    pub viewer_error: Option<String>,
 }
 
 impl ReportsState {
-    pub fn name_changed(&mut self) -> bool {
-        if self.previous_name != self.name {
+    /// Whether the name or archive format changed, either of which changes the report's file name.
+    pub fn file_name_changed(&mut self) -> bool {
+        if self.previous_name != self.name || self.previous_format != self.profile.format {
             self.previous_name = self.name.clone();
+            self.previous_format = self.profile.format;
             return true;
         }
         false
@@ -108,6 +173,8 @@ pub struct VersionsState {
     pub package_status: HashMap<(String, FileInfo), Arc<Mutex<RequestStatus>>>,
     pub installer_status: HashMap<(String, FileInfo), Arc<Mutex<RequestStatus>>>,
     pub which: VersionsTypes,
+    /// Why the last request failed, e.g. missing credentials, rather than showing no versions.
+    pub error: Option<String>,
 
     filters: HashMap<String, VersionsFilter>,
     cache: HashMap<String, Vec<FileInfo>>,
@@ -132,13 +199,23 @@ impl VersionsState {
     }
 
     fn update_branch_names(&mut self) {
-        self.branch_names = match &self.which {
+        let names = match &self.which {
             VersionsTypes::Packages => self.client.get_packages_branch_names(),
             VersionsTypes::Installers => self.client.get_installers_branch_names(),
         };
+        self.branch_names = self.unwrap_or_record(names);
+    }
+
+    /// The value, or the default after recording the error.
+    fn unwrap_or_record<T: Default>(&mut self, result: anyhow::Result<T>) -> T {
+        result.unwrap_or_else(|err| {
+            self.error = Some(err.to_string());
+            T::default()
+        })
     }
 
     pub fn refresh(&mut self) {
+        self.error = None;
         self.update_branch_names();
         self.cache.clear();
         self.update_current_cache_if_needed();
@@ -155,6 +232,8 @@ impl VersionsState {
             VersionsTypes::Packages => self.client.get_packages_info(branch),
             VersionsTypes::Installers => self.client.get_installers_info(branch),
         };
+        // Cached even if empty after an error, so it isn't requested again every frame.
+        let info = self.unwrap_or_record(info);
         self.cache.insert(branch.to_string(), info);
         self.sort_cache_for(branch);
         self.populate_filter_options_for(branch);
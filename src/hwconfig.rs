@@ -1,42 +1,43 @@
 use crate::common::*;
+use crate::config_resolution::{self, ConfigFile};
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub name: String,
+    pub modified: String,
+}
+
 pub fn get_path() -> Option<PathBuf> {
-    for path in valid_paths() {
-        if path.exists() {
-            return Some(path);
-        }
-    }
-    None
+    config_resolution::resolve(ConfigFile::HwConfig)
+        .active()
+        .map(|candidate| candidate.path.clone())
 }
 
 pub fn get_path_or_cwd() -> PathBuf {
     get_path().unwrap_or_else(|| in_cwd(FILE_NAME))
 }
 
+/// Every location SigGen checks, in order.
 pub fn valid_paths() -> Vec<PathBuf> {
-    // TODO: SigGen first checks it's cwd.
-    if cfg!(windows) {
-        vec![dirs::document_dir(), Some(PathBuf::from("E:\\"))]
-            .iter()
-            .flatten()
-            .map(|x| {
-                x.join("Keysight")
-                    .join("PathWave")
-                    .join("SignalGenerator")
-                    .join(FILE_NAME)
-            })
-            .collect()
-    } else {
-        vec![dirs::home_dir()]
-            .iter()
-            .flatten()
-            .map(|x| x.join("userdata").join(FILE_NAME))
-            .collect()
-    }
+    ConfigFile::HwConfig.search_order()
 }
 
-pub fn set_text(path: &Path, text: &String) -> anyhow::Result<()> {
+/// Whatever was in the file before is kept as the `previous` snapshot. Failing to keep it doesn't stop the save,
+/// it's returned as a warning instead.
+pub fn set_text(path: &Path, text: &str) -> anyhow::Result<Option<String>> {
+    let warning = match read_from(path) {
+        Some(previous) if previous != text => keep_snapshot_in(&snapshots_dir(), PREVIOUS, &previous)
+            .err()
+            .map(|err| format!("Unable to keep the previous hwconfig as a snapshot: {}", err)),
+        _ => None,
+    };
+    write(path, text)?;
+    Ok(warning)
+}
+
+fn write(path: &Path, text: &str) -> anyhow::Result<()> {
     std::fs::create_dir_all(path.parent().unwrap()).and_then(|_| std::fs::write(path, text))?;
     Ok(())
 }
@@ -48,4 +49,123 @@ pub fn read_from(path: &Path) -> Option<String> {
     std::fs::read_to_string(path).ok()
 }
 
+pub fn snapshots_dir() -> PathBuf {
+    toolkit_dir().join("hwconfig_snapshots")
+}
+
+pub fn list_snapshots() -> Vec<Snapshot> {
+    let mut snapshots: Vec<(std::time::SystemTime, Snapshot)> = std::fs::read_dir(snapshots_dir())
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().extension().and_then(|x| x.to_str()) == Some(SNAPSHOT_EXTENSION))
+                .filter_map(|entry| {
+                    let modified = entry.metadata().and_then(|x| x.modified()).ok()?;
+                    let name = entry.path().file_stem()?.to_string_lossy().to_string();
+                    Some((
+                        modified,
+                        Snapshot {
+                            name,
+                            modified: chrono::DateTime::<chrono::Local>::from(modified)
+                                .format("%Y-%m-%d %H:%M:%S")
+                                .to_string(),
+                        },
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    snapshots.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    snapshots.into_iter().map(|(_, snapshot)| snapshot).collect()
+}
+
+pub fn read_snapshot(name: &str) -> Option<String> {
+    read_snapshot_in(&snapshots_dir(), name)
+}
+
+pub fn save_snapshot(name: &str, text: &str) -> anyhow::Result<()> {
+    save_snapshot_in(&snapshots_dir(), name, text)
+}
+
+/// Keeps the configuration SigGen reads as the last known good snapshot, returning that path.
+pub fn mark_known_good() -> anyhow::Result<PathBuf> {
+    let path = get_path_or_cwd();
+    mark_known_good_in(&snapshots_dir(), &path)?;
+    Ok(path)
+}
+
+/// Writes the named snapshot to the path SigGen reads, returning that path. Snapshots are left as they were, so
+/// restoring twice gives the same configuration.
+pub fn restore_snapshot(name: &str) -> anyhow::Result<PathBuf> {
+    let path = get_path_or_cwd();
+    restore_snapshot_in(&snapshots_dir(), name, &path)?;
+    Ok(path)
+}
+
+fn read_snapshot_in(dir: &Path, name: &str) -> Option<String> {
+    snapshot_path(dir, name).ok().and_then(|path| read_from(&path))
+}
+
+/// Snapshots the toolkit keeps itself can't be saved over.
+fn save_snapshot_in(dir: &Path, name: &str, text: &str) -> anyhow::Result<()> {
+    if [LAST_KNOWN_GOOD, PREVIOUS].contains(&name) {
+        return Err(anyhow::anyhow!("{} is a reserved snapshot name", name));
+    }
+    keep_snapshot_in(dir, name, text)
+}
+
+fn keep_snapshot_in(dir: &Path, name: &str, text: &str) -> anyhow::Result<()> {
+    let path = snapshot_path(dir, name)?;
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+fn mark_known_good_in(dir: &Path, path: &Path) -> anyhow::Result<()> {
+    let text = read_from(path).ok_or_else(|| anyhow::anyhow!("No hwconfig found at {}", path.display()))?;
+    keep_snapshot_in(dir, LAST_KNOWN_GOOD, &text)
+}
+
+fn restore_snapshot_in(dir: &Path, name: &str, path: &Path) -> anyhow::Result<()> {
+    let text = read_snapshot_in(dir, name).ok_or_else(|| anyhow::anyhow!("No snapshot named {}", name))?;
+    write(path, &text)
+}
+
+fn snapshot_path(dir: &Path, name: &str) -> anyhow::Result<PathBuf> {
+    check_name("snapshot", name)?;
+    Ok(dir.join(format!("{}.{}", name, SNAPSHOT_EXTENSION)))
+}
+
 pub const FILE_NAME: &str = "sghal_dev.cfg";
+pub const LAST_KNOWN_GOOD: &str = "last-known-good";
+/// What the file held before the last save.
+pub const PREVIOUS: &str = "previous";
+const SNAPSHOT_EXTENSION: &str = "cfg";
+
+#[cfg(test)]
+mod tests {
+    use crate::hwconfig::{
+        mark_known_good_in, read_snapshot_in, restore_snapshot_in, save_snapshot_in, write, LAST_KNOWN_GOOD,
+    };
+
+    #[test]
+    fn snapshot_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let snapshots = dir.path().join("snapshots");
+        let config = dir.path().join("sghal_dev.cfg");
+        write(&config, "good").unwrap();
+        mark_known_good_in(&snapshots, &config).unwrap();
+        save_snapshot_in(&snapshots, "named", "named").unwrap();
+        assert_eq!(read_snapshot_in(&snapshots, "named").unwrap(), "named");
+        assert!(save_snapshot_in(&snapshots, "../escape", "x").is_err());
+        assert!(save_snapshot_in(&snapshots, LAST_KNOWN_GOOD, "x").is_err());
+
+        write(&config, "bad").unwrap();
+        for _ in 0..2 {
+            restore_snapshot_in(&snapshots, LAST_KNOWN_GOOD, &config).unwrap();
+            assert_eq!(std::fs::read_to_string(&config).unwrap(), "good");
+            assert_eq!(read_snapshot_in(&snapshots, LAST_KNOWN_GOOD).unwrap(), "good");
+        }
+        assert!(restore_snapshot_in(&snapshots, "missing", &config).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use crate::hwconfig;
+use crate::hwconfig_parser::{self, HwConfig, OPTIONS_KEY};
+use crate::report_archive;
+use crate::report_format::ArchiveFormat;
+use crate::report_profile::Collector;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use strum::{Display, EnumIter, EnumString};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Default, EnumString, EnumIter, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Mode {
+    /// Semantic if both sides parse, lines otherwise.
+    #[default]
+    Auto,
+    Semantic,
+    Lines,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// In semantic mode `address` is a `hwconfig get` key, in line mode it is `line <n>` of the side the text comes from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub address: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Diff {
+    pub mode: Mode,
+    pub changes: Vec<Change>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let old = self.old.as_deref().unwrap_or_default();
+        let new = self.new.as_deref().unwrap_or_default();
+        match self.kind {
+            ChangeKind::Added => write!(f, "+ {}: {}", self.address, new),
+            ChangeKind::Removed => write!(f, "- {}: {}", self.address, old),
+            ChangeKind::Changed => write!(f, "~ {}: {} -> {}", self.address, old, new),
+        }
+    }
+}
+
+/// Reads one side of a diff. `spec` is one of:
+/// - `snapshot:<name>`
+/// - `<report>.zip`, or a tar report, using the sghal_dev.cfg listed in its manifest
+/// - `<report>.zip!<path in archive>`
+/// - any other path
+pub fn read_source(spec: &str) -> anyhow::Result<String> {
+    if let Some(name) = spec.strip_prefix(SNAPSHOT_PREFIX) {
+        return hwconfig::read_snapshot(name).ok_or_else(|| anyhow::anyhow!("No snapshot named {}", name));
+    }
+    let (path, inner) = match spec.rsplit_once('!') {
+        Some((path, inner)) if is_report(path) => (path, Some(inner)),
+        _ => (spec, None),
+    };
+    if is_report(path) {
+        read_from_report(Path::new(path), inner)
+    } else {
+        std::fs::read_to_string(path).map_err(|err| anyhow::anyhow!("{}: {}", path, err))
+    }
+}
+
+fn is_report(path: &str) -> bool {
+    ArchiveFormat::of(Path::new(path)).is_some()
+}
+
+fn read_from_report(path: &Path, inner: Option<&str>) -> anyhow::Result<String> {
+    let name = match inner {
+        Some(name) => name.to_string(),
+        None => report_archive::inspect(path)?
+            .files_of(Collector::HwConfig)
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("{} contains no {}", path.display(), hwconfig::FILE_NAME))?,
+    };
+    report_archive::read_text(path, &name)
+}
+
+pub fn diff(old: &str, new: &str, mode: Mode) -> Diff {
+    let parsed = match mode {
+        Mode::Lines => None,
+        _ => hwconfig_parser::parse(old).ok().zip(hwconfig_parser::parse(new).ok()),
+    };
+    match parsed {
+        Some((old, new)) => Diff {
+            mode: Mode::Semantic,
+            changes: diff_semantic(&old.model(), &new.model()),
+        },
+        None => Diff {
+            mode: Mode::Lines,
+            changes: diff_lines(old, new),
+        },
+    }
+}
+
+/// Compares values by address, so reordering, comments and formatting are ignored. Modules are matched by
+/// name and slot, options are compared as a set.
+fn diff_semantic(old: &HwConfig, new: &HwConfig) -> Vec<Change> {
+    diff_values(&flatten(old), &flatten(new))
+}
+
+/// Compares `(address, value)` pairs. Removed and changed addresses come in `old` order, followed by the added.
+pub fn diff_values(old: &[(String, String)], new: &[(String, String)]) -> Vec<Change> {
+    let mut changes = vec![];
+    for (address, old_value) in old {
+        match new.iter().find(|(x, _)| x == address) {
+            None => changes.push(Change {
+                kind: ChangeKind::Removed,
+                address: address.clone(),
+                old: Some(old_value.clone()),
+                new: None,
+            }),
+            Some((_, new_value)) if new_value != old_value => changes.push(Change {
+                kind: ChangeKind::Changed,
+                address: address.clone(),
+                old: Some(old_value.clone()),
+                new: Some(new_value.clone()),
+            }),
+            _ => {}
+        }
+    }
+    for (address, new_value) in new {
+        if !old.iter().any(|(x, _)| x == address) {
+            changes.push(Change {
+                kind: ChangeKind::Added,
+                address: address.clone(),
+                old: None,
+                new: Some(new_value.clone()),
+            });
+        }
+    }
+    changes
+}
+
+/// `(address, value)` pairs in file order. A repeated key keeps its last value, as SigGen reads it.
+fn flatten(config: &HwConfig) -> Vec<(String, String)> {
+    let mut values: Vec<(String, String)> = vec![];
+    let mut insert = |address: String, value: String| match values.iter_mut().find(|(x, _)| *x == address) {
+        Some(existing) => existing.1 = value,
+        None => values.push((address, value)),
+    };
+    for entry in &config.globals {
+        insert(entry.key.clone(), entry.value.clone());
+    }
+    for (index, module) in config.modules.iter().enumerate() {
+        let section = match module.slot {
+            Some(slot) => format!("{}@{}", module.name, slot),
+            None => {
+                let occurrence = config.modules[..index]
+                    .iter()
+                    .filter(|x| x.name == module.name && x.slot.is_none())
+                    .count();
+                match occurrence {
+                    0 => module.name.clone(),
+                    n => format!("{}#{}", module.name, n + 1),
+                }
+            }
+        };
+        for entry in &module.entries {
+            let value = if entry.key == OPTIONS_KEY {
+                let mut options = module.options.clone();
+                options.sort();
+                options.join(", ")
+            } else {
+                entry.value.clone()
+            };
+            insert(format!("{}.{}", section, entry.key), value);
+        }
+    }
+    values
+}
+
+/// Longest common subsequence diff. Removed lines are numbered in `old`, added lines in `new`.
+fn diff_lines(old: &str, new: &str) -> Vec<Change> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            changes.push(Change {
+                kind: ChangeKind::Removed,
+                address: format!("line {}", i + 1),
+                old: Some(old[i].to_string()),
+                new: None,
+            });
+            i += 1;
+        } else {
+            changes.push(Change {
+                kind: ChangeKind::Added,
+                address: format!("line {}", j + 1),
+                old: None,
+                new: Some(new[j].to_string()),
+            });
+            j += 1;
+        }
+    }
+    changes
+}
+
+pub const SNAPSHOT_PREFIX: &str = "snapshot:";
+
+#[cfg(test)]
+mod tests {
+    use crate::hwconfig_diff::{diff, ChangeKind, Mode};
+
+    #[test]
+    fn semantic() {
+        let old = "simulated = true\n[M9484C]\nslot = 1\noptions = 1EA, UNT\n[M9410A]\nslot = 2\n";
+        let new = "# Comment\n[M9484C]\nslot = 1\noptions = UNT,1EA\nfoo = bar\nsimulated = false\n";
+        let result = diff(old, new, Mode::Auto);
+        assert_eq!(result.mode, Mode::Semantic);
+        let changes: Vec<String> = result.changes.iter().map(|x| x.to_string()).collect();
+        assert_eq!(
+            changes,
+            vec![
+                "- simulated: true",
+                "- M9410A@2.slot: 2",
+                "+ M9484C@1.foo: bar",
+                "+ M9484C@1.simulated: false",
+            ]
+        );
+    }
+
+    #[test]
+    fn lines() {
+        let result = diff("a = 1\n[\nc = 3\n", "a = 1\nc = 3\nd = 4\n", Mode::Auto);
+        assert_eq!(result.mode, Mode::Lines);
+        let kinds: Vec<(ChangeKind, &str)> = result.changes.iter().map(|x| (x.kind, x.address.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![(ChangeKind::Removed, "line 2"), (ChangeKind::Added, "line 3")]
+        );
+    }
+}
@@ -0,0 +1,489 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A parsed sghal_dev.cfg. Unmodified lines are written back exactly as they were read, along with their line
+/// endings and whether the file ends with one.
+///
+/// The format is line based:
+/// ```text
+/// # Comment
+/// globalKey = value
+/// [M9484C]
+/// slot = 2
+/// options = 1EA, UNT  ; Inline comment
+/// key = value
+/// ```
+/// Each `[section]` describes one simulated module. `slot` and `options` are interpreted, every other
+/// entry is kept as plain text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Document {
+    lines: Vec<Line>,
+    /// Each line's ending, empty for a last line without one.
+    endings: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Line {
+    Blank(String),
+    Comment(String),
+    Section {
+        name: String,
+        raw: String,
+    },
+    Entry {
+        key: String,
+        value: String,
+        comment: Option<String>,
+        raw: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct HwConfig {
+    pub globals: Vec<Entry>,
+    pub modules: Vec<Module>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Module {
+    pub name: String,
+    pub line: usize,
+    pub slot: Option<u32>,
+    pub options: Vec<String>,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Entry {
+    pub key: String,
+    pub value: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxErrors(pub Vec<SyntaxError>);
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl fmt::Display for SyntaxErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<String> = self.0.iter().map(|error| error.to_string()).collect();
+        write!(f, "{}", messages.join("\n"))
+    }
+}
+
+impl std::error::Error for SyntaxErrors {}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (line, ending) in self.lines.iter().zip(&self.endings) {
+            match line {
+                Line::Blank(raw) | Line::Comment(raw) | Line::Section { raw, .. } => write!(f, "{}", raw)?,
+                Line::Entry { raw: Some(raw), .. } => write!(f, "{}", raw)?,
+                Line::Entry {
+                    key, value, comment, ..
+                } => match comment {
+                    Some(comment) => write!(f, "{} = {} {}", key, value, comment)?,
+                    None => write!(f, "{} = {}", key, value)?,
+                },
+            }
+            f.write_str(ending)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn parse(text: &str) -> Result<Document, SyntaxErrors> {
+    let mut document = Document::default();
+    let mut errors = vec![];
+    let mut in_module = false;
+
+    for (index, raw) in text.split_inclusive('\n').enumerate() {
+        let (raw, ending) = match raw.strip_suffix("\r\n") {
+            Some(raw) => (raw, "\r\n"),
+            None => match raw.strip_suffix('\n') {
+                Some(raw) => (raw, "\n"),
+                None => (raw, ""),
+            },
+        };
+        let number = index + 1;
+        let indent = raw.chars().take_while(|c| c.is_whitespace()).count();
+        let trimmed = raw.trim();
+
+        if trimmed.is_empty() {
+            document.lines.push(Line::Blank(raw.to_string()));
+        } else if is_comment(trimmed) {
+            document.lines.push(Line::Comment(raw.to_string()));
+        } else if let Some(rest) = trimmed.strip_prefix('[') {
+            match rest.find(']') {
+                None => errors.push(SyntaxError {
+                    line: number,
+                    column: indent + trimmed.chars().count() + 1,
+                    message: "expected ']' to close section".to_string(),
+                }),
+                Some(end) => {
+                    let name = rest[..end].trim();
+                    let trailing = rest[end + 1..].trim();
+                    if name.is_empty() {
+                        errors.push(SyntaxError {
+                            line: number,
+                            column: indent + 2,
+                            message: "section name is empty".to_string(),
+                        });
+                    } else if !trailing.is_empty() && !is_comment(trailing) {
+                        errors.push(SyntaxError {
+                            line: number,
+                            column: indent + rest[..end].chars().count() + 3,
+                            message: "unexpected text after section".to_string(),
+                        });
+                    } else {
+                        in_module = true;
+                        document.lines.push(Line::Section {
+                            name: name.to_string(),
+                            raw: raw.to_string(),
+                        });
+                    }
+                }
+            }
+        } else {
+            match trimmed.find('=') {
+                None => errors.push(SyntaxError {
+                    line: number,
+                    column: indent + 1,
+                    message: "expected 'key = value', a [section] or a comment".to_string(),
+                }),
+                Some(0) => errors.push(SyntaxError {
+                    line: number,
+                    column: indent + 1,
+                    message: "key is empty".to_string(),
+                }),
+                Some(equals) => {
+                    let key = trimmed[..equals].trim().to_string();
+                    let (value, comment) = split_comment(&trimmed[equals + 1..]);
+                    let value_column = indent + trimmed[..equals + 1].chars().count() + 2;
+                    if in_module && key == SLOT_KEY && value.parse::<u32>().is_err() {
+                        errors.push(SyntaxError {
+                            line: number,
+                            column: value_column,
+                            message: format!("slot must be a non-negative integer, found '{}'", value),
+                        });
+                    }
+                    document.lines.push(Line::Entry {
+                        key,
+                        value,
+                        comment,
+                        raw: Some(raw.to_string()),
+                    });
+                }
+            }
+        }
+        if document.endings.len() < document.lines.len() {
+            document.endings.push(ending);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(document)
+    } else {
+        Err(SyntaxErrors(errors))
+    }
+}
+
+impl Document {
+    pub fn model(&self) -> HwConfig {
+        let mut config = HwConfig::default();
+        for (index, line) in self.lines.iter().enumerate() {
+            match line {
+                Line::Section { name, .. } => config.modules.push(Module {
+                    name: name.clone(),
+                    line: index + 1,
+                    ..Default::default()
+                }),
+                Line::Entry { key, value, .. } => {
+                    let entry = Entry {
+                        key: key.clone(),
+                        value: value.clone(),
+                        line: index + 1,
+                    };
+                    match config.modules.last_mut() {
+                        None => config.globals.push(entry),
+                        Some(module) => {
+                            if key == SLOT_KEY {
+                                module.slot = value.parse().ok();
+                            } else if key == OPTIONS_KEY {
+                                module.options = split_options(value);
+                            }
+                            module.entries.push(entry);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        config
+    }
+
+    /// Sections and entries without comments, blank lines or formatting. Two documents describing the
+    /// same hardware produce the same result.
+    pub fn normalized(&self) -> Vec<String> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                Line::Section { name, .. } => Some(format!("[{}]", name)),
+                Line::Entry { key, value, .. } if key == OPTIONS_KEY => {
+                    Some(format!("{} = {}", key, split_options(value).join(", ")))
+                }
+                Line::Entry { key, value, .. } => Some(format!("{} = {}", key, value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Addresses are `key` for global entries and `section.key` for module entries. Sections sharing a
+    /// name can be told apart by slot: `section@2.key`.
+    pub fn get(&self, address: &str) -> anyhow::Result<Option<String>> {
+        let (section, key) = self.resolve(address)?;
+        Ok(self.find_entry(section, key).and_then(|index| match &self.lines[index] {
+            Line::Entry { value, .. } => Some(value.clone()),
+            _ => None,
+        }))
+    }
+
+    pub fn set(&mut self, address: &str, value: &str) -> anyhow::Result<()> {
+        let (section, key) = self.resolve(address)?;
+        let key = key.to_string();
+        if let Some(index) = self.find_entry(section, &key) {
+            self.set_value_at(index, value);
+            return Ok(());
+        }
+
+        let new_entry = Line::Entry {
+            key: key.clone(),
+            value: value.to_string(),
+            comment: None,
+            raw: None,
+        };
+        let insert_at = match section {
+            Some(section) => self.section_end(Some(section)),
+            None if address.contains('.') => {
+                let (name, key) = address.split_once('.').unwrap();
+                self.insert_line(
+                    self.lines.len(),
+                    Line::Section {
+                        name: name.to_string(),
+                        raw: format!("[{}]", name),
+                    },
+                );
+                self.insert_line(
+                    self.lines.len(),
+                    Line::Entry {
+                        key: key.to_string(),
+                        value: value.to_string(),
+                        comment: None,
+                        raw: None,
+                    },
+                );
+                return Ok(());
+            }
+            None => self.section_end(None),
+        };
+        self.insert_line(insert_at, new_entry);
+        Ok(())
+    }
+
+    /// Inserts a line ending like the file's first line. A line added after a last line without an ending
+    /// takes over not having one.
+    fn insert_line(&mut self, index: usize, line: Line) {
+        let newline = self.endings.iter().copied().find(|x| !x.is_empty()).unwrap_or("\n");
+        let mut ending = newline;
+        if index == self.lines.len() {
+            if let Some(last) = self.endings.last_mut().filter(|x| x.is_empty()) {
+                *last = newline;
+                ending = "";
+            }
+        }
+        self.lines.insert(index, line);
+        self.endings.insert(index, ending);
+    }
+
+    /// Replaces the value of the entry on a 1-based line number, as reported by [`Document::model`].
+    pub fn set_line_value(&mut self, line: usize, value: &str) {
+        if line > 0 && line <= self.lines.len() {
+            self.set_value_at(line - 1, value);
+        }
+    }
+
+    fn set_value_at(&mut self, index: usize, new_value: &str) {
+        if let Line::Entry { value, raw, .. } = &mut self.lines[index] {
+            if value != new_value {
+                *value = new_value.to_string();
+                *raw = None;
+            }
+        }
+    }
+
+    /// Returns the section's line index, or `None` for the global section, along with the key.
+    fn resolve<'a>(&self, address: &'a str) -> anyhow::Result<(Option<usize>, &'a str)> {
+        for (dot, _) in address.match_indices('.') {
+            if let Some(section) = self.find_section(&address[..dot])? {
+                return Ok((Some(section), &address[dot + 1..]));
+            }
+        }
+        Ok((None, address))
+    }
+
+    fn find_section(&self, selector: &str) -> anyhow::Result<Option<usize>> {
+        let (name, slot) = match selector.split_once('@') {
+            Some((name, slot)) => {
+                let slot = slot
+                    .parse::<u32>()
+                    .map_err(|_| anyhow::anyhow!("Invalid slot in '{}', expected section@<number>", selector))?;
+                (name, Some(slot))
+            }
+            None => (selector, None),
+        };
+        let config = self.model();
+        Ok(config
+            .modules
+            .iter()
+            .find(|module| module.name == name && (slot.is_none() || module.slot == slot))
+            .map(|module| module.line - 1))
+    }
+
+    fn find_entry(&self, section: Option<usize>, wanted: &str) -> Option<usize> {
+        let start = section.map_or(0, |index| index + 1);
+        self.lines[start..]
+            .iter()
+            .take_while(|line| !matches!(line, Line::Section { .. }))
+            .position(|line| matches!(line, Line::Entry { key, .. } if key == wanted))
+            .map(|offset| start + offset)
+    }
+
+    /// Index just past the last entry of a section, or of the global entries for `None`, so new entries land
+    /// before trailing blank lines.
+    fn section_end(&self, section: Option<usize>) -> usize {
+        let start = section.map_or(0, |index| index + 1);
+        let mut end = start;
+        for (offset, line) in self.lines[start..].iter().enumerate() {
+            match line {
+                Line::Section { .. } => break,
+                Line::Entry { .. } => end = start + offset + 1,
+                _ => {}
+            }
+        }
+        end
+    }
+}
+
+fn is_comment(text: &str) -> bool {
+    text.starts_with('#') || text.starts_with(';')
+}
+
+fn split_comment(text: &str) -> (String, Option<String>) {
+    let comment_start = text
+        .char_indices()
+        .zip(text.chars().skip(1))
+        .find(|((_, c), next)| c.is_whitespace() && (*next == '#' || *next == ';'))
+        .map(|((index, _), _)| index);
+    match comment_start {
+        Some(index) => (text[..index].trim().to_string(), Some(text[index..].trim().to_string())),
+        None => (text.trim().to_string(), None),
+    }
+}
+
+pub fn split_options(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|option| !option.is_empty())
+        .map(|option| option.to_string())
+        .collect()
+}
+
+pub const SLOT_KEY: &str = "slot";
+pub const OPTIONS_KEY: &str = "options";
+
+#[cfg(test)]
+mod tests {
+    use crate::hwconfig_parser::parse;
+
+    const SAMPLE: &str = "# Simulated hardware\n\
+                          simulated = true\n\
+                          \n\
+                          [M9484C]\n\
+                          slot = 2\n\
+                          options = 1EA, UNT  ; licensed\n\
+                          serial = MY1234\n";
+
+    #[test]
+    fn model() {
+        let config = parse(SAMPLE).unwrap().model();
+        assert_eq!(config.globals.len(), 1);
+        assert_eq!(config.globals[0].line, 2);
+        assert_eq!(config.modules.len(), 1);
+        assert_eq!(config.modules[0].slot, Some(2));
+        assert_eq!(config.modules[0].options, vec!["1EA", "UNT"]);
+        assert_eq!(config.modules[0].entries[2].line, 7);
+    }
+
+    #[test]
+    fn round_trip_preserves_comments() {
+        let mut document = parse(SAMPLE).unwrap();
+        assert_eq!(document.to_string(), SAMPLE);
+        document.set("M9484C.options", "1EA").unwrap();
+        assert!(document.to_string().contains("options = 1EA ; licensed\n"));
+        assert!(document.to_string().starts_with("# Simulated hardware\n"));
+    }
+
+    #[test]
+    fn get_and_set() {
+        let mut document = parse(SAMPLE).unwrap();
+        assert_eq!(document.get("simulated").unwrap(), Some("true".to_string()));
+        assert_eq!(document.get("M9484C@2.serial").unwrap(), Some("MY1234".to_string()));
+        assert_eq!(document.get("M9484C@3.serial").unwrap(), None);
+        assert!(document.get("M9484C@two.serial").is_err());
+        assert!(document.set("M9484C@two.serial", "MY5678").is_err());
+        document.set("M9484C.power", "on").unwrap();
+        document.set("debug", "1").unwrap();
+        document.set("M9410A.slot", "4").unwrap();
+        let text = document.to_string();
+        assert!(text.contains("simulated = true\ndebug = 1\n"));
+        assert!(text.contains("serial = MY1234\npower = on\n"));
+        assert!(text.ends_with("[M9410A]\nslot = 4\n"));
+    }
+
+    #[test]
+    fn round_trip_preserves_line_endings() {
+        let crlf = SAMPLE.replace('\n', "\r\n");
+        let mut document = parse(&crlf).unwrap();
+        assert_eq!(document.to_string(), crlf);
+        document.set("M9484C.power", "on").unwrap();
+        assert!(document.to_string().ends_with("serial = MY1234\r\npower = on\r\n"));
+
+        let unterminated = SAMPLE.trim_end();
+        let mut document = parse(unterminated).unwrap();
+        assert_eq!(document.to_string(), unterminated);
+        document.set("M9484C.power", "on").unwrap();
+        assert!(document.to_string().ends_with("serial = MY1234\npower = on"));
+    }
+
+    #[test]
+    fn errors() {
+        let errors = parse("[M9484C\nslot = two\njunk\n= 1\n[]").unwrap_err().0;
+        let positions: Vec<(usize, usize)> = errors.iter().map(|e| (e.line, e.column)).collect();
+        assert_eq!(positions, vec![(1, 8), (3, 1), (4, 1), (5, 2)]);
+
+        let errors = parse("[M9484C]\nslot = two").unwrap_err().0;
+        assert_eq!((errors[0].line, errors[0].column), (2, 8));
+    }
+}
@@ -0,0 +1,161 @@
+use crate::common::*;
+use crate::hwconfig_parser;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A named sghal_dev.cfg. User profiles are `<name>.cfg` files whose first comment line is the description.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub description: String,
+    pub text: String,
+    /// Built-in profiles are examples that can't be saved over.
+    pub builtin: bool,
+}
+
+pub fn profiles_dir() -> PathBuf {
+    toolkit_dir().join("hwconfig_profiles")
+}
+
+/// Built-in profiles followed by the ones saved in [`profiles_dir`], by name. Saved files named like a built-in
+/// profile are left out.
+pub fn list_profiles() -> Vec<Profile> {
+    let mut user: Vec<Profile> = glob::glob(profiles_dir().join("*.cfg").to_string_lossy().as_ref())
+        .map(|glob| {
+            glob.flatten()
+                .filter_map(|path| {
+                    let text = std::fs::read_to_string(&path).ok()?;
+                    Some(Profile {
+                        name: path.file_stem()?.to_string_lossy().to_string(),
+                        description: description_of(&text),
+                        text,
+                        builtin: false,
+                    })
+                })
+                .filter(|profile| !is_builtin(&profile.name))
+                .collect()
+        })
+        .unwrap_or_default();
+    user.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut profiles: Vec<Profile> = BUILTIN_PROFILES
+        .iter()
+        .map(|(name, text)| Profile {
+            name: name.to_string(),
+            description: description_of(text),
+            text: text.to_string(),
+            builtin: true,
+        })
+        .collect();
+    profiles.append(&mut user);
+    profiles
+}
+
+pub fn get_profile(name: &str) -> Option<Profile> {
+    list_profiles().into_iter().find(|profile| profile.name == name)
+}
+
+/// Saves `text` as a user profile, with `description` as its first line if given.
+pub fn save_profile(name: &str, description: Option<&str>, text: &str) -> anyhow::Result<PathBuf> {
+    check_name("profile", name)?;
+    if is_builtin(name) {
+        return Err(anyhow::anyhow!("{} is a built-in profile and can't be saved over", name));
+    }
+    let text = match description {
+        Some(description) => format!("# {}\n{}", description, text),
+        None => text.to_string(),
+    };
+    let path = profiles_dir().join(format!("{}.cfg", name));
+    std::fs::create_dir_all(profiles_dir())?;
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
+
+/// The first profile describing the same hardware as `text`, ignoring comments and formatting.
+pub fn matching_profile<'a>(profiles: &'a [Profile], text: &str) -> Option<&'a Profile> {
+    let wanted = normalized(text);
+    profiles.iter().find(|profile| normalized(&profile.text) == wanted)
+}
+
+fn normalized(text: &str) -> Vec<String> {
+    match hwconfig_parser::parse(text) {
+        Ok(document) => document.normalized(),
+        Err(_) => text
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect(),
+    }
+}
+
+fn is_builtin(name: &str) -> bool {
+    BUILTIN_PROFILES.iter().any(|(builtin, _)| *builtin == name)
+}
+
+fn description_of(text: &str) -> String {
+    text.lines()
+        .map(|line| line.trim())
+        .find(|line| line.starts_with('#') || line.starts_with(';'))
+        .map(|line| line.trim_start_matches(['#', ';']).trim().to_string())
+        .unwrap_or_default()
+}
+
+const BUILTIN_PROFILES: [(&str, &str); 2] = [
+    (
+        "single-vxg",
+        "# Simulated M9484C VXG in slot 1\n\
+         simulated = true\n\
+         \n\
+         [M9484C]\n\
+         slot = 1\n",
+    ),
+    (
+        "dual-vxg",
+        "# Simulated M9484C VXGs in slots 1 and 2\n\
+         simulated = true\n\
+         \n\
+         [M9484C]\n\
+         slot = 1\n\
+         \n\
+         [M9484C]\n\
+         slot = 2\n",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::hwconfig_profiles::{description_of, matching_profile, save_profile, Profile};
+
+    fn profile(name: &str, text: &str) -> Profile {
+        Profile {
+            name: name.to_string(),
+            description: description_of(text),
+            text: text.to_string(),
+            builtin: false,
+        }
+    }
+
+    #[test]
+    fn matching() {
+        let profiles = [
+            profile("one", "[M9484C]\nslot = 1\noptions = 1EA, UNT\n"),
+            profile("two", "[M9484C]\nslot = 2\n"),
+        ];
+        let edited = "# Edited by hand\n\n[M9484C]  ; first\nslot=1\noptions = 1EA UNT\n";
+        assert_eq!(matching_profile(&profiles, edited).unwrap().name, "one");
+        assert_eq!(matching_profile(&profiles, "[M9484C]\nslot = 3\n"), None);
+        assert_eq!(matching_profile(&profiles, "not [valid\n  slot = 2"), None);
+    }
+
+    #[test]
+    fn builtin_profiles_are_read_only() {
+        assert!(save_profile("single-vxg", None, "").is_err());
+        assert!(save_profile("../escape", None, "").is_err());
+    }
+
+    #[test]
+    fn descriptions() {
+        assert_eq!(description_of("\n  ;  Bench 4 setup \n# second\n[M9484C]\n"), "Bench 4 setup");
+        assert_eq!(description_of("[M9484C]\nslot = 1 # inline\n"), "");
+    }
+}
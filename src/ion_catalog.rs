@@ -0,0 +1,245 @@
+use crate::{common, ion_diagnostics, logging, report};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use strum::{Display, EnumString};
+
+/// Known Ion setting paths and operation names, harvested from SigGen state files and trace logs.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Catalog {
+    pub setting_paths: BTreeSet<String>,
+    pub operation_names: BTreeSet<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum CatalogKind {
+    Settings,
+    Operations,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Validation {
+    Known,
+    Unknown,
+    /// Nothing to check against: either the entry or the relevant part of the catalogue is empty.
+    Unchecked,
+}
+
+impl Catalog {
+    /// Loads the persisted catalogue, merges in everything found on this machine and saves the result.
+    /// Entries are never dropped, so names survive log rotation.
+    pub fn refresh(extra_logs: &[PathBuf]) -> Self {
+        let mut catalog = Self::load();
+        catalog.harvest(extra_logs);
+        let _ = catalog.save();
+        catalog
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(cache_path())
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = cache_path();
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Reads at most `MAX_HARVEST_BYTES` of each file: state files over the limit are skipped, since a cut off
+    /// document wouldn't parse, and only the end of longer logs is read, where the newest names are.
+    pub fn harvest(&mut self, extra_logs: &[PathBuf]) {
+        for path in report::get_data_dir_state_file_paths() {
+            if std::fs::metadata(&path).is_ok_and(|x| x.len() <= MAX_HARVEST_BYTES) {
+                if let Ok(text) = std::fs::read_to_string(&path) {
+                    self.harvest_state_file(&text);
+                }
+            }
+        }
+        for path in trace_log_paths().iter().chain(extra_logs) {
+            if let Ok(reader) = log_tail(path) {
+                self.harvest_trace_log(reader);
+            }
+        }
+    }
+
+    /// State files are JSON documents. Every leaf is a setting; its path is the chain of keys leading to it.
+    pub fn harvest_state_file(&mut self, text: &str) {
+        if let Ok(json) = serde_json::from_str::<serde_json::Value>(text) {
+            collect_leaf_paths(&json, "", &mut self.setting_paths);
+        }
+    }
+
+    /// Ion traces quote the names they refer to, e.g. `Setting 'Source.Frequency' set by user`.
+    pub fn harvest_trace_log(&mut self, reader: impl BufRead) {
+        for line in reader.split(b'\n').map_while(Result::ok) {
+            let line = String::from_utf8_lossy(&line);
+            self.setting_paths.extend(quoted_after(&line, "setting"));
+            self.operation_names.extend(quoted_after(&line, "operation"));
+        }
+    }
+
+    pub fn entries(&self, kind: CatalogKind) -> &BTreeSet<String> {
+        match kind {
+            CatalogKind::Settings => &self.setting_paths,
+            CatalogKind::Operations => &self.operation_names,
+        }
+    }
+
+    /// Entries starting with `prefix` come first, followed by entries merely containing it. Case-insensitive.
+    pub fn suggest(&self, kind: CatalogKind, prefix: &str, limit: usize) -> Vec<String> {
+        let needle = prefix.to_lowercase();
+        let (starts, contains): (Vec<&String>, Vec<&String>) = self
+            .entries(kind)
+            .iter()
+            .filter(|entry| entry.to_lowercase().contains(&needle))
+            .partition(|entry| entry.to_lowercase().starts_with(&needle));
+        starts.into_iter().chain(contains).take(limit).cloned().collect()
+    }
+
+    /// Entries may be glob patterns, which are known if they match at least one catalogued name.
+    pub fn validate(&self, kind: CatalogKind, entry: &str) -> Validation {
+        let entries = self.entries(kind);
+        if entry.is_empty() || entries.is_empty() {
+            return Validation::Unchecked;
+        }
+        let known = match glob::Pattern::new(entry) {
+            Ok(pattern) if entry.contains(['*', '?', '[']) => entries.iter().any(|x| pattern.matches(x)),
+            _ => entries.contains(entry),
+        };
+        if known {
+            Validation::Known
+        } else {
+            Validation::Unknown
+        }
+    }
+}
+
+fn collect_leaf_paths(value: &serde_json::Value, prefix: &str, out: &mut BTreeSet<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                collect_leaf_paths(child, &path, out);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_leaf_paths(item, prefix, out);
+            }
+        }
+        _ if !prefix.is_empty() => {
+            out.insert(prefix.to_string());
+        }
+        _ => {}
+    }
+}
+
+fn quoted_after(line: &str, keyword: &str) -> Option<String> {
+    let lower = line.to_ascii_lowercase();
+    let start = lower.find(keyword)? + keyword.len();
+    let rest = line[start..].trim_start_matches(|c: char| c.is_whitespace() || c == ':');
+    let quote = rest.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    let rest = &rest[1..];
+    let name = &rest[..rest.find(quote)?];
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+fn trace_log_paths() -> Vec<PathBuf> {
+    let mut paths = vec![logging::get_log_path_from_current_config()];
+    if let Some(dir) = ion_diagnostics::get_debug_dir() {
+        for pattern in ["*.log", "*.txt"] {
+            if let Ok(glob) = glob::glob(dir.join(pattern).to_string_lossy().as_ref()) {
+                paths.extend(glob.flatten());
+            }
+        }
+    }
+    paths
+}
+
+/// The last `MAX_HARVEST_BYTES` of the log, starting at a whole line.
+fn log_tail(path: &Path) -> std::io::Result<impl BufRead> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut reader = BufReader::new(if len > MAX_HARVEST_BYTES {
+        file.seek(SeekFrom::Start(len - MAX_HARVEST_BYTES))?;
+        file.take(MAX_HARVEST_BYTES)
+    } else {
+        file.take(len)
+    });
+    if len > MAX_HARVEST_BYTES {
+        reader.read_until(b'\n', &mut vec![])?;
+    }
+    Ok(reader)
+}
+
+fn cache_path() -> PathBuf {
+    common::toolkit_dir().join(CACHE_FILE_NAME)
+}
+
+const CACHE_FILE_NAME: &str = "ion_catalog.json";
+const MAX_HARVEST_BYTES: u64 = 16 * 1024 * 1024;
+
+#[cfg(test)]
+mod tests {
+    use crate::ion_catalog::{Catalog, CatalogKind, Validation};
+
+    #[test]
+    fn state_file_leaves() {
+        let mut catalog = Catalog::default();
+        catalog.harvest_state_file(r#"{"Source": {"Frequency": 1e9, "Power": [{"Level": -10}]}, "Mode": "CW"}"#);
+        let paths: Vec<&str> = catalog.setting_paths.iter().map(|x| x.as_str()).collect();
+        assert_eq!(paths, vec!["Mode", "Source.Frequency", "Source.Power.Level"]);
+    }
+
+    #[test]
+    fn trace_log_names() {
+        let mut catalog = Catalog::default();
+        catalog.harvest_trace_log(
+            "[2021-01-01 00:00:00.000] [ion] [trace] Setting 'Source.Frequency' set by user\n\
+             [2021-01-01 00:00:00.000] [ion] [trace] Operation: \"Arm\" resolved\n\
+             [2021-01-01 00:00:00.000] [siggen] [info] setting up the thing"
+                .as_bytes(),
+        );
+        assert!(catalog.setting_paths.contains("Source.Frequency"));
+        assert!(catalog.operation_names.contains("Arm"));
+        assert_eq!(catalog.setting_paths.len(), 1);
+    }
+
+    #[test]
+    fn validate_and_suggest() {
+        let mut catalog = Catalog::default();
+        assert_eq!(
+            catalog.validate(CatalogKind::Settings, "Anything"),
+            Validation::Unchecked
+        );
+        catalog.setting_paths.insert("Source.Frequency".to_string());
+        catalog.setting_paths.insert("Modulation.Source".to_string());
+        assert_eq!(
+            catalog.validate(CatalogKind::Settings, "Source.Frequency"),
+            Validation::Known
+        );
+        assert_eq!(catalog.validate(CatalogKind::Settings, "Source.*"), Validation::Known);
+        assert_eq!(
+            catalog.validate(CatalogKind::Settings, "Source.Freq"),
+            Validation::Unknown
+        );
+        assert_eq!(
+            catalog.suggest(CatalogKind::Settings, "source", 5),
+            vec!["Source.Frequency", "Modulation.Source"]
+        );
+    }
+}
@@ -1,5 +1,6 @@
+use crate::common::*;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 #[serde(rename_all = "PascalCase")]
@@ -218,6 +219,17 @@ pub const FILE_NAME: &str = "ionDebug.json";
 pub const ENV_VAR: &str = "ION_DEBUG_DIR";
 pub const CONFLUENCE_URL: &str = "https://confluence.it.keysight.com/display/PWL/Ion+Diagnostics";
 
+pub fn get_debug_dir() -> Option<PathBuf> {
+    std::env::var(ENV_VAR).ok().map(PathBuf::from)
+}
+
+pub fn get_config_path_or_cwd() -> PathBuf {
+    match get_debug_dir().map(|dir| dir.join(FILE_NAME)) {
+        Some(path) if path.exists() => path,
+        _ => in_cwd(FILE_NAME),
+    }
+}
+
 pub fn get_config_from(path: &Path) -> Option<DiagnosticsConfiguration> {
     std::fs::read_to_string(path)
         .ok()
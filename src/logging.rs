@@ -1,4 +1,5 @@
 use crate::common::*;
+use crate::config_resolution::{self, ConfigFile};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
@@ -173,37 +174,18 @@ pub enum Template {
 }
 
 pub fn get_config_path() -> Option<PathBuf> {
-    for path in valid_paths() {
-        if path.exists() {
-            return Some(path);
-        }
-    }
-    None
+    config_resolution::resolve(ConfigFile::Logging)
+        .active()
+        .map(|candidate| candidate.path.clone())
 }
 
 pub fn get_config_path_or_cwd() -> PathBuf {
     get_config_path().unwrap_or_else(|| in_cwd(FILE_NAME))
 }
 
+/// Every location SigGen checks, in order.
 pub fn valid_paths() -> Vec<PathBuf> {
-    if cfg!(windows) {
-        vec![dirs::document_dir(), Some(PathBuf::from("E:\\"))]
-            .iter()
-            .flatten()
-            .map(|x| {
-                x.join("Keysight")
-                    .join("PathWave")
-                    .join("SignalGenerator")
-                    .join(FILE_NAME)
-            })
-            .collect()
-    } else {
-        vec![dirs::home_dir()]
-            .iter()
-            .flatten()
-            .map(|x| x.join("userdata").join(FILE_NAME))
-            .collect()
-    }
+    ConfigFile::Logging.search_order()
 }
 
 pub fn get_config_from(path: &Path) -> Option<LoggingConfiguration> {
@@ -234,6 +216,75 @@ pub fn get_log_path_from_current_config() -> PathBuf {
         .unwrap_or_else(|| get_code_defined_log_path())
 }
 
+/// Every existing file written by the active config's file sinks: rotated files up to `max_files` and daily
+/// files from the last `days` days. Without file sinks, the code defined log path.
+pub fn get_log_file_paths(days: u32) -> Vec<PathBuf> {
+    let sinks = get_config_from(&get_config_path_or_cwd())
+        .map(|config| config.sinks)
+        .unwrap_or_default();
+    let today = chrono::Local::now().date_naive();
+    let mut candidates: Vec<PathBuf> = sinks
+        .iter()
+        .flat_map(|sink| sink_file_names(sink, days, today))
+        .collect();
+    if candidates.is_empty() {
+        candidates.push(get_code_defined_log_path());
+    }
+
+    let mut paths = vec![];
+    for path in candidates {
+        if path.is_file() && !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+/// The file each sink currently writes to, whether or not it exists yet.
+pub fn get_active_log_file_paths() -> Vec<PathBuf> {
+    let sinks = get_config_from(&get_config_path_or_cwd())
+        .map(|config| config.sinks)
+        .unwrap_or_default();
+    let today = chrono::Local::now().date_naive();
+    let mut paths: Vec<PathBuf> = sinks
+        .iter()
+        .filter_map(|sink| sink_file_names(sink, 1, today).into_iter().next())
+        .collect();
+    if paths.is_empty() {
+        paths.push(get_code_defined_log_path());
+    }
+    paths.dedup();
+    paths
+}
+
+/// The names spdlog gives a sink's files, newest first: `log.txt`, `log.1.txt`... for rotating sinks and
+/// `log_2021-01-31.txt`, `log_2021-01-30.txt`... for daily sinks.
+pub fn sink_file_names(sink: &Sink, days: u32, today: chrono::NaiveDate) -> Vec<PathBuf> {
+    match sink {
+        Sink::File { file_name, .. } => vec![PathBuf::from(file_name)],
+        Sink::RotatingFile {
+            file_name, max_files, ..
+        } => std::iter::once(PathBuf::from(file_name))
+            .chain((1..=max_files.unwrap_or(u8::MAX)).map(|i| with_suffix(file_name, &format!(".{}", i))))
+            .collect(),
+        Sink::DailyFile { file_name, .. } => (0..days)
+            .map(|day| today - chrono::Duration::days(day as i64))
+            .map(|date| with_suffix(file_name, &format!("_{}", date.format("%Y-%m-%d"))))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// `dir/log.txt` becomes `dir/log<suffix>.txt`.
+fn with_suffix(file_name: &str, suffix: &str) -> PathBuf {
+    let path = Path::new(file_name);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    match path.extension() {
+        Some(extension) => path.with_file_name(format!("{}{}.{}", stem, suffix, extension.to_string_lossy())),
+        None => path.with_file_name(format!("{}{}", stem, suffix)),
+    }
+}
+
 pub fn get_exception_log_path() -> PathBuf {
     PathBuf::from(EXCEPTION_LOG_PATH)
 }
@@ -402,3 +453,41 @@ pub fn get_template(template: &Template) -> LoggingConfiguration {
         Template::Licensing => TEMPLATE_LICENSING.clone(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::logging::{sink_file_names, Level, Sink};
+    use std::path::PathBuf;
+
+    #[test]
+    fn rotating_and_daily_names() {
+        let rotating = Sink::RotatingFile {
+            level: Level::Trace,
+            name: "rotating".to_string(),
+            file_name: "/logs/sg.log".to_string(),
+            truncate: None,
+            max_size: None,
+            max_files: Some(2),
+        };
+        let today = chrono::NaiveDate::from_ymd_opt(2021, 3, 1).unwrap();
+        assert_eq!(
+            sink_file_names(&rotating, 7, today),
+            vec![
+                PathBuf::from("/logs/sg.log"),
+                PathBuf::from("/logs/sg.1.log"),
+                PathBuf::from("/logs/sg.2.log")
+            ]
+        );
+
+        let daily = Sink::DailyFile {
+            level: Level::Trace,
+            name: "daily".to_string(),
+            file_name: "/logs/sg.log".to_string(),
+            truncate: None,
+        };
+        assert_eq!(
+            sink_file_names(&daily, 2, today),
+            vec![PathBuf::from("/logs/sg_2021-03-01.log"), PathBuf::from("/logs/sg_2021-02-28.log")]
+        );
+    }
+}
@@ -7,14 +7,30 @@ use structopt::StructOpt;
 
 mod cli;
 mod common;
+mod config_resolution;
+mod credentials;
 mod gui;
 mod gui_state;
 mod hwconfig;
+mod hwconfig_diff;
+mod hwconfig_parser;
+mod hwconfig_profiles;
+mod ion_catalog;
 mod ion_diagnostics;
 mod log_viewer;
 mod logging;
+mod redaction;
+mod remotes;
 mod report;
+mod report_archive;
+mod report_compare;
+mod report_encryption;
+mod report_format;
+mod report_profile;
+mod report_upload;
+mod report_watch;
 mod server;
+mod system_info;
 mod versions;
 mod model;
 
@@ -27,6 +43,8 @@ fn main() -> anyhow::Result<()> {
         println!();
     }
 
+    remotes::select(args.remote.as_deref(), args.artifactory_url.as_deref())?;
+
     match args.command {
         None => gui::run(Box::new(NativeModel::default())),
         Some(Command::Backend) => {
@@ -4,28 +4,67 @@ use std::{
 };
 
 use crate::{
-    common, hwconfig,
+    common,
+    config_resolution::{self, ConfigFile, Resolution},
+    hwconfig::{self, Snapshot},
+    hwconfig_diff,
+    hwconfig_profiles::{self, Profile},
+    ion_catalog::Catalog,
+    ion_diagnostics::{self, DiagnosticsConfiguration},
     logging::{self, LoggingConfiguration, Template},
-    report, versions,
+    report::{self, StoredReport},
+    report_archive::{self, ReportInfo},
+    report_format::ArchiveFormat,
+    report_profile::{self, ReportProfile},
+    versions,
 };
 
-pub trait Model {
+/// Shared with the GUI's background threads.
+pub trait Model: Send + Sync {
     fn name(&self) -> &str;
     fn file_exists(&self, path: &Path) -> bool;
+    fn delete_file(&self, path: &Path) -> anyhow::Result<()>;
+    fn config_resolve(&self, file: ConfigFile) -> Resolution;
     fn logging_get_config_path(&self) -> Option<PathBuf>;
     fn logging_valid_paths(&self) -> Vec<PathBuf>;
     fn logging_get_config_from(&self, path: &Path) -> Option<LoggingConfiguration>;
-    fn logging_get_log_path_from_current_config(&self) -> PathBuf;
+    fn logging_get_log_file_paths(&self, days: u32) -> Vec<PathBuf>;
     fn logging_set_config(&self, path: &Path, config: LoggingConfiguration) -> anyhow::Result<()>;
     fn logging_get_template(&self, template: &Template) -> LoggingConfiguration;
+    fn ion_diagnostics_get_debug_dir(&self) -> Option<PathBuf>;
+    fn ion_diagnostics_get_config_from(&self, path: &Path) -> Option<DiagnosticsConfiguration>;
+    fn ion_diagnostics_set_config(&self, path: &Path, config: DiagnosticsConfiguration) -> anyhow::Result<()>;
+    fn ion_diagnostics_get_catalog(&self) -> Catalog;
     fn get_cwd(&self) -> PathBuf;
+    /// Whether the backend is this process, so paths it gives are on this machine.
+    fn is_local(&self) -> bool;
     fn get_code_defined_log_path(&self) -> PathBuf;
     fn get_exception_log_path(&self) -> PathBuf;
     fn report_get_data_dir_state_file_paths(&self) -> Vec<String>;
-    fn report_zip_file_name(&self, name: &str) -> String;
-    fn report_create_report(&self, name: &str) -> anyhow::Result<()>;
+    fn report_file_name(&self, name: &str, format: ArchiveFormat) -> String;
+    /// Returns the path of the report on this machine, downloaded from the backend if it ran there.
+    fn report_create_report(&self, name: &str, profile: &ReportProfile) -> anyhow::Result<PathBuf>;
+    fn report_list_stored(&self) -> anyhow::Result<Vec<StoredReport>>;
+    /// Returns the path of a local copy of the stored report.
+    fn report_fetch_stored(&self, name: &str) -> anyhow::Result<PathBuf>;
+    fn report_delete_stored(&self, name: &str) -> anyhow::Result<()>;
+    fn report_get_profile(&self) -> ReportProfile;
+    fn report_set_profile(&self, profile: &ReportProfile) -> anyhow::Result<()>;
+    fn report_get_no_reset_system_settings_path(&self) -> PathBuf;
+    fn report_inspect(&self, zip: &Path) -> anyhow::Result<ReportInfo>;
+    fn report_read_text(&self, zip: &Path, name: &str) -> anyhow::Result<String>;
     fn versions_download_dir(&self, branch: &str) -> PathBuf;
     fn hwconfig_get_path(&self) -> Option<PathBuf>;
+    fn hwconfig_valid_paths(&self) -> Vec<PathBuf>;
+    fn hwconfig_read_from(&self, path: &Path) -> Option<String>;
+    /// Returns a warning if the previous content couldn't be kept as a snapshot.
+    fn hwconfig_set_text(&self, path: &Path, text: &str) -> anyhow::Result<Option<String>>;
+    fn hwconfig_list_snapshots(&self) -> Vec<Snapshot>;
+    fn hwconfig_read_snapshot(&self, name: &str) -> Option<String>;
+    fn hwconfig_save_snapshot(&self, name: &str, text: &str) -> anyhow::Result<()>;
+    fn hwconfig_mark_known_good(&self) -> anyhow::Result<PathBuf>;
+    fn hwconfig_list_profiles(&self) -> Vec<Profile>;
+    fn hwconfig_read_source(&self, spec: &str) -> anyhow::Result<String>;
     fn installed_version(&self) -> Option<String>;
 }
 
@@ -46,6 +85,15 @@ impl Model for NativeModel {
         path.exists() && path.is_file()
     }
 
+    fn delete_file(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+
+    fn config_resolve(&self, file: ConfigFile) -> Resolution {
+        config_resolution::resolve(file)
+    }
+
     fn logging_get_config_path(&self) -> Option<PathBuf> {
         logging::get_config_path()
     }
@@ -58,8 +106,8 @@ impl Model for NativeModel {
         logging::get_config_from(path)
     }
 
-    fn logging_get_log_path_from_current_config(&self) -> PathBuf {
-        logging::get_log_path_from_current_config()
+    fn logging_get_log_file_paths(&self, days: u32) -> Vec<PathBuf> {
+        logging::get_log_file_paths(days)
     }
 
     fn logging_set_config(&self, path: &Path, config: LoggingConfiguration) -> anyhow::Result<()> {
@@ -71,10 +119,30 @@ impl Model for NativeModel {
         logging::get_template(template)
     }
 
+    fn ion_diagnostics_get_debug_dir(&self) -> Option<PathBuf> {
+        ion_diagnostics::get_debug_dir()
+    }
+
+    fn ion_diagnostics_get_config_from(&self, path: &Path) -> Option<DiagnosticsConfiguration> {
+        ion_diagnostics::get_config_from(path)
+    }
+
+    fn ion_diagnostics_set_config(&self, path: &Path, config: DiagnosticsConfiguration) -> anyhow::Result<()> {
+        ion_diagnostics::set_config(path, config)
+    }
+
+    fn ion_diagnostics_get_catalog(&self) -> Catalog {
+        Catalog::refresh(&[])
+    }
+
     fn get_cwd(&self) -> PathBuf {
         common::in_cwd(PathBuf::new())
     }
 
+    fn is_local(&self) -> bool {
+        true
+    }
+
     fn get_exception_log_path(&self) -> PathBuf {
         logging::get_exception_log_path()
     }
@@ -87,12 +155,44 @@ impl Model for NativeModel {
         report::get_data_dir_state_file_paths()
     }
 
-    fn report_zip_file_name(&self, name: &str) -> String {
-        report::zip_file_name(name)
+    fn report_file_name(&self, name: &str, format: ArchiveFormat) -> String {
+        report::report_file_name(name, format)
+    }
+
+    fn report_create_report(&self, name: &str, profile: &ReportProfile) -> anyhow::Result<PathBuf> {
+        report::create_default_report(name, profile)
+    }
+
+    fn report_list_stored(&self) -> anyhow::Result<Vec<StoredReport>> {
+        report::stored_reports()
+    }
+
+    fn report_fetch_stored(&self, name: &str) -> anyhow::Result<PathBuf> {
+        report::stored_report_path(name)
+    }
+
+    fn report_delete_stored(&self, name: &str) -> anyhow::Result<()> {
+        report::delete_stored_report(name)
+    }
+
+    fn report_get_profile(&self) -> ReportProfile {
+        report_profile::load(&report_profile::default_profile_path()).unwrap_or_default()
     }
 
-    fn report_create_report(&self, name: &str) -> anyhow::Result<()> {
-        report::create_report(name)
+    fn report_set_profile(&self, profile: &ReportProfile) -> anyhow::Result<()> {
+        report_profile::save(&report_profile::default_profile_path(), profile)
+    }
+
+    fn report_get_no_reset_system_settings_path(&self) -> PathBuf {
+        report::get_no_reset_system_settings_path()
+    }
+
+    fn report_inspect(&self, zip: &Path) -> anyhow::Result<ReportInfo> {
+        report_archive::inspect(zip)
+    }
+
+    fn report_read_text(&self, zip: &Path, name: &str) -> anyhow::Result<String> {
+        report_archive::read_text(zip, name)
     }
 
     fn versions_download_dir(&self, branch: &str) -> PathBuf {
@@ -103,6 +203,42 @@ impl Model for NativeModel {
         hwconfig::get_path()
     }
 
+    fn hwconfig_valid_paths(&self) -> Vec<PathBuf> {
+        hwconfig::valid_paths()
+    }
+
+    fn hwconfig_read_from(&self, path: &Path) -> Option<String> {
+        hwconfig::read_from(path)
+    }
+
+    fn hwconfig_set_text(&self, path: &Path, text: &str) -> anyhow::Result<Option<String>> {
+        hwconfig::set_text(path, text)
+    }
+
+    fn hwconfig_list_snapshots(&self) -> Vec<Snapshot> {
+        hwconfig::list_snapshots()
+    }
+
+    fn hwconfig_read_snapshot(&self, name: &str) -> Option<String> {
+        hwconfig::read_snapshot(name)
+    }
+
+    fn hwconfig_save_snapshot(&self, name: &str, text: &str) -> anyhow::Result<()> {
+        hwconfig::save_snapshot(name, text)
+    }
+
+    fn hwconfig_mark_known_good(&self) -> anyhow::Result<PathBuf> {
+        hwconfig::mark_known_good()
+    }
+
+    fn hwconfig_list_profiles(&self) -> Vec<Profile> {
+        hwconfig_profiles::list_profiles()
+    }
+
+    fn hwconfig_read_source(&self, spec: &str) -> anyhow::Result<String> {
+        hwconfig_diff::read_source(spec)
+    }
+
     fn installed_version(&self) -> Option<String> {
         versions::installed_version()
     }
@@ -146,6 +282,47 @@ impl Model for HttpClientModel {
         }
     }
 
+    fn delete_file(&self, path: &Path) -> anyhow::Result<()> {
+        #[cfg(debug_assertions)]
+        println!("Sending delete_file request: {}", path.to_string_lossy());
+        let response = self
+            .client
+            .delete(format!(
+                "{}{}/{}",
+                "http://",
+                self.url,
+                &format!("delete-file{}", path.to_string_lossy())
+            ))
+            .send();
+        match response {
+            Ok(response) => {
+                response.error_for_status()?;
+                Ok(())
+            }
+            Err(err) => {
+                println!("{:?}", err);
+                Result::Err(err.into())
+            }
+        }
+    }
+
+    fn config_resolve(&self, file: ConfigFile) -> Resolution {
+        #[cfg(debug_assertions)]
+        println!("Sending config_resolve request: {}", file);
+        let response = self
+            .create_get_request(&format!("config-resolution/{}", file))
+            .send();
+        match response {
+            Ok(response) => serde_json::from_str(&response.text().unwrap_or_default())
+                .ok()
+                .unwrap_or_default(),
+            Err(err) => {
+                println!("{:?}", err);
+                Default::default()
+            }
+        }
+    }
+
     fn logging_get_config_path(&self) -> Option<PathBuf> {
         #[cfg(debug_assertions)]
         println!("Sending logging_get_path request");
@@ -191,10 +368,10 @@ impl Model for HttpClientModel {
         }
     }
 
-    fn logging_get_log_path_from_current_config(&self) -> PathBuf {
+    fn logging_get_log_file_paths(&self, days: u32) -> Vec<PathBuf> {
         #[cfg(debug_assertions)]
-        println!("Sending logging_get_log_path_from_current_config request");
-        match self.create_get_request("logging/log-path-from-current-config").send() {
+        println!("Sending logging_get_log_file_paths request: {}", days);
+        match self.create_get_request(&format!("logging/log-files/{}", days)).send() {
             Ok(response) => serde_json::from_str(&response.text().unwrap_or_default())
                 .ok()
                 .unwrap_or_default(),
@@ -244,6 +421,78 @@ impl Model for HttpClientModel {
         }
     }
 
+    fn ion_diagnostics_get_debug_dir(&self) -> Option<PathBuf> {
+        #[cfg(debug_assertions)]
+        println!("Sending ion_diagnostics_get_debug_dir request");
+        let response = self.create_get_request("ion-diagnostics/debug-dir").send();
+        match response {
+            Ok(response) => serde_json::from_str(&response.text().unwrap_or_default())
+                .ok()
+                .unwrap_or_default(),
+            Err(err) => {
+                println!("{:?}", err);
+                Default::default()
+            }
+        }
+    }
+
+    fn ion_diagnostics_get_config_from(&self, path: &Path) -> Option<DiagnosticsConfiguration> {
+        #[cfg(debug_assertions)]
+        println!("Sending ion_diagnostics_get_config_from request: {}", path.to_string_lossy());
+        let response = self
+            .create_get_request(&format!("ion-diagnostics/config{}", path.to_string_lossy()))
+            .send();
+        match response {
+            Ok(response) => serde_json::from_str(&response.text().unwrap_or_default())
+                .ok()
+                .unwrap_or_default(),
+            Err(err) => {
+                println!("{:?}", err);
+                Default::default()
+            }
+        }
+    }
+
+    fn ion_diagnostics_set_config(&self, path: &Path, config: DiagnosticsConfiguration) -> anyhow::Result<()> {
+        #[cfg(debug_assertions)]
+        println!("Sending ion_diagnostics_set_config request: {}", path.to_string_lossy());
+        let response = self
+            .client
+            .post(format!(
+                "{}{}/{}",
+                "http://",
+                self.url,
+                &format!("ion-diagnostics/config{}", path.to_string_lossy())
+            ))
+            .body(serde_json::to_string(&config)?)
+            .send();
+        match response {
+            Ok(response) => {
+                response.error_for_status()?;
+                Ok(())
+            }
+            Err(err) => {
+                println!("{:?}", err);
+                Result::Err(err.into())
+            }
+        }
+    }
+
+    fn ion_diagnostics_get_catalog(&self) -> Catalog {
+        #[cfg(debug_assertions)]
+        println!("Sending ion_diagnostics_get_catalog request");
+        let response = self.create_get_request("ion-diagnostics/catalog").send();
+        match response {
+            Ok(response) => serde_json::from_str(&response.text().unwrap_or_default())
+                .ok()
+                .unwrap_or_default(),
+            Err(err) => {
+                println!("{:?}", err);
+                Default::default()
+            }
+        }
+    }
+
     fn get_cwd(&self) -> PathBuf {
         #[cfg(debug_assertions)]
         println!("Sending get_cwd request");
@@ -259,6 +508,10 @@ impl Model for HttpClientModel {
         }
     }
 
+    fn is_local(&self) -> bool {
+        false
+    }
+
     fn get_exception_log_path(&self) -> PathBuf {
         #[cfg(debug_assertions)]
         println!("Sending get_exception_log_path request");
@@ -304,11 +557,12 @@ impl Model for HttpClientModel {
         }
     }
 
-    fn report_zip_file_name(&self, name: &str) -> String {
+    fn report_file_name(&self, name: &str, format: ArchiveFormat) -> String {
         #[cfg(debug_assertions)]
-        println!("Sending report_zip_file_name request: {}", name);
+        println!("Sending report_file_name request: {}", name);
         let response = self
-            .create_get_request(&format!("reports/zip-file-name/{}", name))
+            .create_get_request(&format!("reports/file-name/{}", name))
+            .query(&[("format", format.to_string())])
             .send();
         match response {
             Ok(response) => serde_json::from_str(&response.text().unwrap_or_default())
@@ -321,7 +575,7 @@ impl Model for HttpClientModel {
         }
     }
 
-    fn report_create_report(&self, name: &str) -> anyhow::Result<()> {
+    fn report_create_report(&self, name: &str, profile: &ReportProfile) -> anyhow::Result<PathBuf> {
         #[cfg(debug_assertions)]
         println!("Sending report_create_report request: {}", name);
         let response = self
@@ -332,9 +586,107 @@ impl Model for HttpClientModel {
                 self.url,
                 &format!("reports/create/{}", name)
             ))
+            .body(serde_json::to_string(profile)?)
             .send();
+        let created: PathBuf = match response {
+            Ok(response) if response.status().is_success() => serde_json::from_str(&response.text()?)?,
+            Ok(response) => return Err(anyhow::anyhow!(response.text()?)),
+            Err(err) => {
+                println!("{:?}", err);
+                return Result::Err(err.into());
+            }
+        };
+        let name = created
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("Invalid report path {}", created.display()))?;
+        self.report_fetch_stored(&name.to_string_lossy())
+    }
+
+    fn report_list_stored(&self) -> anyhow::Result<Vec<StoredReport>> {
+        #[cfg(debug_assertions)]
+        println!("Sending report_list_stored request");
+        let response = self.create_get_request("reports/stored").send();
         match response {
-            Ok(_) => Ok(()),
+            Ok(response) if response.status().is_success() => Ok(serde_json::from_str(&response.text()?)?),
+            Ok(response) => Err(anyhow::anyhow!(response.text().unwrap_or_default())),
+            Err(err) => {
+                println!("{:?}", err);
+                Result::Err(err.into())
+            }
+        }
+    }
+
+    /// Saves the report in the working directory, replacing a file of the same name like creating it does.
+    fn report_fetch_stored(&self, name: &str) -> anyhow::Result<PathBuf> {
+        #[cfg(debug_assertions)]
+        println!("Sending report_fetch_stored request: {}", name);
+        let mut response = match self.create_get_request(&format!("reports/stored/{}", name)).send() {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => return Err(anyhow::anyhow!(response.text().unwrap_or_default())),
+            Err(err) => {
+                println!("{:?}", err);
+                return Result::Err(err.into());
+            }
+        };
+        let path = common::in_cwd(name);
+        let partial = PathBuf::from(format!("{}.partial", path.display()));
+        let result = (|| -> anyhow::Result<()> {
+            let mut file = std::fs::File::create(&partial)?;
+            response.copy_to(&mut file)?;
+            std::fs::rename(&partial, &path)?;
+            Ok(())
+        })();
+        if result.is_err() {
+            let _ = std::fs::remove_file(&partial);
+        }
+        result.map(|_| path)
+    }
+
+    fn report_delete_stored(&self, name: &str) -> anyhow::Result<()> {
+        #[cfg(debug_assertions)]
+        println!("Sending report_delete_stored request: {}", name);
+        let response = self
+            .client
+            .delete(format!("{}{}/{}", "http://", self.url, &format!("reports/stored/{}", name)))
+            .send();
+        match response {
+            Ok(response) if response.status().is_success() => Ok(()),
+            Ok(response) => Err(anyhow::anyhow!(response.text().unwrap_or_default())),
+            Err(err) => {
+                println!("{:?}", err);
+                Result::Err(err.into())
+            }
+        }
+    }
+
+    fn report_get_profile(&self) -> ReportProfile {
+        #[cfg(debug_assertions)]
+        println!("Sending report_get_profile request");
+        let response = self.create_get_request("reports/profile").send();
+        match response {
+            Ok(response) => serde_json::from_str(&response.text().unwrap_or_default())
+                .ok()
+                .unwrap_or_default(),
+            Err(err) => {
+                println!("{:?}", err);
+                Default::default()
+            }
+        }
+    }
+
+    fn report_set_profile(&self, profile: &ReportProfile) -> anyhow::Result<()> {
+        #[cfg(debug_assertions)]
+        println!("Sending report_set_profile request");
+        let response = self
+            .client
+            .post(format!("{}{}/{}", "http://", self.url, "reports/profile"))
+            .body(serde_json::to_string(profile)?)
+            .send();
+        match response {
+            Ok(response) => {
+                response.error_for_status()?;
+                Ok(())
+            }
             Err(err) => {
                 println!("{:?}", err);
                 Result::Err(err.into())
@@ -342,6 +694,21 @@ impl Model for HttpClientModel {
         }
     }
 
+    fn report_get_no_reset_system_settings_path(&self) -> PathBuf {
+        #[cfg(debug_assertions)]
+        println!("Sending report_get_no_reset_system_settings_path request");
+        let response = self.create_get_request("reports/no-reset-system-settings-path").send();
+        match response {
+            Ok(response) => serde_json::from_str(&response.text().unwrap_or_default())
+                .ok()
+                .unwrap_or_default(),
+            Err(err) => {
+                println!("{:?}", err);
+                Default::default()
+            }
+        }
+    }
+
     fn versions_download_dir(&self, branch: &str) -> PathBuf {
         #[cfg(debug_assertions)]
         println!("Sending versions_download_dir request");
@@ -374,6 +741,200 @@ impl Model for HttpClientModel {
         }
     }
 
+    fn hwconfig_valid_paths(&self) -> Vec<PathBuf> {
+        #[cfg(debug_assertions)]
+        println!("Sending hwconfig_valid_paths request");
+        let response = self.create_get_request("hwconfig/valid-paths").send();
+        match response {
+            Ok(response) => serde_json::from_str(&response.text().unwrap_or_default())
+                .ok()
+                .unwrap_or_default(),
+            Err(err) => {
+                println!("{:?}", err);
+                Default::default()
+            }
+        }
+    }
+
+    fn hwconfig_read_from(&self, path: &Path) -> Option<String> {
+        #[cfg(debug_assertions)]
+        println!("Sending hwconfig_read_from request: {}", path.to_string_lossy());
+        let response = self
+            .create_get_request(&format!("hwconfig/text{}", path.to_string_lossy()))
+            .send();
+        match response {
+            Ok(response) => serde_json::from_str(&response.text().unwrap_or_default())
+                .ok()
+                .unwrap_or_default(),
+            Err(err) => {
+                println!("{:?}", err);
+                Default::default()
+            }
+        }
+    }
+
+    fn hwconfig_set_text(&self, path: &Path, text: &str) -> anyhow::Result<Option<String>> {
+        #[cfg(debug_assertions)]
+        println!("Sending hwconfig_set_text request: {}", path.to_string_lossy());
+        let response = self
+            .client
+            .post(format!(
+                "{}{}/{}",
+                "http://",
+                self.url,
+                &format!("hwconfig/text{}", path.to_string_lossy())
+            ))
+            .body(text.to_string())
+            .send();
+        match response {
+            Ok(response) => Ok(serde_json::from_str(&response.error_for_status()?.text()?)?),
+            Err(err) => {
+                println!("{:?}", err);
+                Result::Err(err.into())
+            }
+        }
+    }
+
+    fn report_inspect(&self, zip: &Path) -> anyhow::Result<ReportInfo> {
+        #[cfg(debug_assertions)]
+        println!("Sending report_inspect request: {}", zip.display());
+        let response = self
+            .create_get_request("reports/inspect")
+            .query(&[("zip", zip.to_string_lossy().as_ref())])
+            .send();
+        match response {
+            Ok(response) if response.status().is_success() => Ok(serde_json::from_str(&response.text()?)?),
+            Ok(response) => Err(anyhow::anyhow!(response.text().unwrap_or_default())),
+            Err(err) => {
+                println!("{:?}", err);
+                Result::Err(err.into())
+            }
+        }
+    }
+
+    fn report_read_text(&self, zip: &Path, name: &str) -> anyhow::Result<String> {
+        #[cfg(debug_assertions)]
+        println!("Sending report_read_text request: {} {}", zip.display(), name);
+        let response = self
+            .create_get_request("reports/file")
+            .query(&[("zip", zip.to_string_lossy().as_ref()), ("name", name)])
+            .send();
+        match response {
+            Ok(response) if response.status().is_success() => Ok(response.text()?),
+            Ok(response) => Err(anyhow::anyhow!(response.text().unwrap_or_default())),
+            Err(err) => {
+                println!("{:?}", err);
+                Result::Err(err.into())
+            }
+        }
+    }
+
+    fn hwconfig_read_source(&self, spec: &str) -> anyhow::Result<String> {
+        #[cfg(debug_assertions)]
+        println!("Sending hwconfig_read_source request: {}", spec);
+        let response = self
+            .create_get_request("hwconfig/source")
+            .query(&[("spec", spec)])
+            .send();
+        match response {
+            Ok(response) if response.status().is_success() => Ok(response.text()?),
+            Ok(response) => Err(anyhow::anyhow!(response.text().unwrap_or_default())),
+            Err(err) => {
+                println!("{:?}", err);
+                Result::Err(err.into())
+            }
+        }
+    }
+
+    fn hwconfig_list_snapshots(&self) -> Vec<Snapshot> {
+        #[cfg(debug_assertions)]
+        println!("Sending hwconfig_list_snapshots request");
+        let response = self.create_get_request("hwconfig/snapshots").send();
+        match response {
+            Ok(response) => serde_json::from_str(&response.text().unwrap_or_default())
+                .ok()
+                .unwrap_or_default(),
+            Err(err) => {
+                println!("{:?}", err);
+                Default::default()
+            }
+        }
+    }
+
+    fn hwconfig_read_snapshot(&self, name: &str) -> Option<String> {
+        #[cfg(debug_assertions)]
+        println!("Sending hwconfig_read_snapshot request: {}", name);
+        let response = self
+            .create_get_request(&format!("hwconfig/snapshots/{}", name))
+            .send();
+        match response {
+            Ok(response) => serde_json::from_str(&response.text().unwrap_or_default())
+                .ok()
+                .unwrap_or_default(),
+            Err(err) => {
+                println!("{:?}", err);
+                Default::default()
+            }
+        }
+    }
+
+    fn hwconfig_save_snapshot(&self, name: &str, text: &str) -> anyhow::Result<()> {
+        #[cfg(debug_assertions)]
+        println!("Sending hwconfig_save_snapshot request: {}", name);
+        let response = self
+            .client
+            .post(format!(
+                "{}{}/{}",
+                "http://",
+                self.url,
+                &format!("hwconfig/snapshots/{}", name)
+            ))
+            .body(text.to_string())
+            .send();
+        match response {
+            Ok(response) => {
+                response.error_for_status()?;
+                Ok(())
+            }
+            Err(err) => {
+                println!("{:?}", err);
+                Result::Err(err.into())
+            }
+        }
+    }
+
+    fn hwconfig_mark_known_good(&self) -> anyhow::Result<PathBuf> {
+        #[cfg(debug_assertions)]
+        println!("Sending hwconfig_mark_known_good request");
+        let response = self
+            .client
+            .post(format!("{}{}/{}", "http://", self.url, "hwconfig/known-good"))
+            .send();
+        match response {
+            Ok(response) if response.status().is_success() => Ok(serde_json::from_str(&response.text()?)?),
+            Ok(response) => Err(anyhow::anyhow!(response.text()?)),
+            Err(err) => {
+                println!("{:?}", err);
+                Result::Err(err.into())
+            }
+        }
+    }
+
+    fn hwconfig_list_profiles(&self) -> Vec<Profile> {
+        #[cfg(debug_assertions)]
+        println!("Sending hwconfig_list_profiles request");
+        let response = self.create_get_request("hwconfig/profiles").send();
+        match response {
+            Ok(response) => serde_json::from_str(&response.text().unwrap_or_default())
+                .ok()
+                .unwrap_or_default(),
+            Err(err) => {
+                println!("{:?}", err);
+                Default::default()
+            }
+        }
+    }
+
     fn installed_version(&self) -> Option<String> {
         #[cfg(debug_assertions)]
         println!("Sending installed_version request");
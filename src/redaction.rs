@@ -0,0 +1,165 @@
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// A user-defined rule from the report profile. Without a replacement, matches become `<redacted>`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RedactionRule {
+    pub name: String,
+    pub pattern: String,
+    #[serde(default)]
+    pub replacement: Option<String>,
+}
+
+/// Rules applied, and how many matches each replaced, for the report manifest.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RedactionSummary {
+    pub rules: Vec<String>,
+    pub counts: BTreeMap<String, usize>,
+}
+
+struct Rule {
+    name: String,
+    regex: Regex,
+    replacement: String,
+    /// Filters out matches the regex alone can't rule out.
+    accept: fn(&str) -> bool,
+}
+
+/// Applies the built-in rules followed by the user's rules. Rules run in order on the output of the previous one.
+pub struct Redactor {
+    rules: Vec<Rule>,
+    summary: RedactionSummary,
+}
+
+impl Redactor {
+    pub fn new(host_name: Option<&str>, user_rules: &[RedactionRule]) -> anyhow::Result<Self> {
+        let mut rules = vec![
+            builtin("email", r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}", "<email>"),
+            builtin("mac", r"\b(?:[0-9A-Fa-f]{2}[:-]){5}[0-9A-Fa-f]{2}\b", "<mac>"),
+            Rule {
+                accept: is_ipv6,
+                ..builtin("ipv6", r"(?i)[0-9a-f]{0,4}(?::[0-9a-f]{0,4}){2,7}", "<ipv6>")
+            },
+            builtin(
+                "ipv4",
+                r"\b(?:(?:25[0-5]|2[0-4]\d|1?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|1?\d?\d)\b",
+                "<ipv4>",
+            ),
+            builtin("user-path", r#"(?i)([\\/](?:Users|home)[\\/])[^\\/\s'"]+"#, "${1}<user>"),
+        ];
+        if let Some(host_name) = host_name.filter(|x| !x.is_empty()) {
+            rules.push(builtin(
+                "host-name",
+                &format!(r"(?i)\b{}\b", regex::escape(host_name)),
+                "<host-name>",
+            ));
+        }
+        for rule in user_rules {
+            rules.push(Rule {
+                name: rule.name.clone(),
+                regex: Regex::new(&rule.pattern)
+                    .map_err(|err| anyhow::anyhow!("Redaction rule {}: {}", rule.name, err))?,
+                replacement: rule.replacement.clone().unwrap_or_else(|| "<redacted>".to_string()),
+                accept: |_| true,
+            });
+        }
+        let summary = RedactionSummary {
+            rules: rules.iter().map(|rule| rule.name.clone()).collect(),
+            counts: Default::default(),
+        };
+        Ok(Self { rules, summary })
+    }
+
+    /// Returns the redacted text and the number of replacements made.
+    pub fn redact(&mut self, text: &str) -> (String, usize) {
+        let mut text = text.to_string();
+        let mut total = 0;
+        for rule in &self.rules {
+            let mut count = 0;
+            text = rule
+                .regex
+                .replace_all(&text, |captures: &Captures| {
+                    let matched = captures.get(0).unwrap().as_str();
+                    if (rule.accept)(matched) {
+                        count += 1;
+                        let mut replacement = String::new();
+                        captures.expand(&rule.replacement, &mut replacement);
+                        replacement
+                    } else {
+                        matched.to_string()
+                    }
+                })
+                .to_string();
+            if count > 0 {
+                *self.summary.counts.entry(rule.name.clone()).or_default() += count;
+                total += count;
+            }
+        }
+        (text, total)
+    }
+
+    /// Text is redacted, anything that isn't UTF-8 is returned as is.
+    pub fn redact_bytes(&mut self, bytes: Vec<u8>) -> (Vec<u8>, usize) {
+        match String::from_utf8(bytes) {
+            Ok(text) if !text.contains('\0') => {
+                let (text, count) = self.redact(&text);
+                (text.into_bytes(), count)
+            }
+            Ok(text) => (text.into_bytes(), 0),
+            Err(err) => (err.into_bytes(), 0),
+        }
+    }
+
+    pub fn summary(&self) -> &RedactionSummary {
+        &self.summary
+    }
+}
+
+fn builtin(name: &str, pattern: &str, replacement: &str) -> Rule {
+    Rule {
+        name: name.to_string(),
+        regex: Regex::new(pattern).unwrap(),
+        replacement: replacement.to_string(),
+        accept: |_| true,
+    }
+}
+
+/// The IPv6 pattern also matches times and `Namespace::Name`, so require a real address with a digit in it.
+fn is_ipv6(text: &str) -> bool {
+    std::net::Ipv6Addr::from_str(text).is_ok() && text.contains(|c: char| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::redaction::{RedactionRule, Redactor};
+
+    #[test]
+    fn builtin_rules() {
+        let mut redactor = Redactor::new(Some("LAB-PC"), &[]).unwrap();
+        let (text, count) = redactor.redact(
+            "[12:34:56.789] lab-pc connected from 192.168.0.12 (fe80::1ff:fe23:4567:890a, 00:1A:2B:3C:4D:5E)\n\
+             Sghal::Setup wrote C:\\Users\\jdoe\\Documents\\sghal_dev.cfg, mail jane.doe@example.com",
+        );
+        assert_eq!(
+            text,
+            "[12:34:56.789] <host-name> connected from <ipv4> (<ipv6>, <mac>)\n\
+             Sghal::Setup wrote C:\\Users\\<user>\\Documents\\sghal_dev.cfg, mail <email>"
+        );
+        assert_eq!(count, 6);
+        assert_eq!(redactor.summary().counts.get("ipv4"), Some(&1));
+    }
+
+    #[test]
+    fn user_rules() {
+        let rules = vec![RedactionRule {
+            name: "license-server".to_string(),
+            pattern: r"27000@[\w.-]+".to_string(),
+            replacement: None,
+        }];
+        let mut redactor = Redactor::new(None, &rules).unwrap();
+        assert_eq!(redactor.redact("LM_LICENSE_FILE=27000@flexlm").0, "LM_LICENSE_FILE=<redacted>");
+        assert!(Redactor::new(None, &[RedactionRule { pattern: "(".to_string(), ..rules[0].clone() }]).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use crate::common::toolkit_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// An Artifactory server and where SigGen's files are kept on it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Remote {
+    /// Artifactory's file URL, ending in `/artifactory`.
+    pub url: String,
+    /// Repository paths below `url`, one folder per branch for packages and installers.
+    pub packages: String,
+    pub installers: String,
+    pub reports: String,
+}
+
+impl Default for Remote {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_URL.to_string(),
+            packages: format!(
+                "generic-local-pwsg/siggen/{}",
+                if cfg!(windows) { "packages" } else { "packages-linux" }
+            ),
+            installers: "generic-local-boxer-releases/siggen".to_string(),
+            reports: "generic-local-pwsg/siggen/reports".to_string(),
+        }
+    }
+}
+
+/// The sgt settings file. Remotes leave out the paths that match the default layout.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    /// Used when neither `--remote` nor `SGT_REMOTE` name one.
+    pub default_remote: Option<String>,
+    pub remotes: BTreeMap<String, Remote>,
+}
+
+pub fn settings_path() -> PathBuf {
+    std::env::var_os(SETTINGS_VARIABLE)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| toolkit_dir().join(SETTINGS_FILE_NAME))
+}
+
+/// A missing file is the default settings, with only the built-in remote.
+pub fn load_settings() -> anyhow::Result<Settings> {
+    let path = settings_path();
+    if !path.exists() {
+        return Ok(Settings::default());
+    }
+    let text = std::fs::read_to_string(&path)?;
+    serde_json::from_str(&text).map_err(|err| anyhow::anyhow!("{}: {}", path.display(), err))
+}
+
+/// The built-in remote and the configured ones, which can replace it.
+pub fn remotes(settings: &Settings) -> BTreeMap<String, Remote> {
+    let mut remotes = BTreeMap::from([(DEFAULT_REMOTE.to_string(), Remote::default())]);
+    remotes.extend(settings.remotes.clone());
+    remotes
+}
+
+/// Picks the remote named by `name`, `SGT_REMOTE` or the settings, in that order, with its URL replaced by
+/// `url` or `SGT_ARTIFACTORY_URL` if given.
+pub fn resolve(name: Option<&str>, url: Option<&str>) -> anyhow::Result<(String, Remote)> {
+    let settings = load_settings()?;
+    let name = name
+        .map(str::to_string)
+        .or_else(|| std::env::var(REMOTE_VARIABLE).ok())
+        .or_else(|| settings.default_remote.clone())
+        .unwrap_or_else(|| DEFAULT_REMOTE.to_string());
+    let remotes = remotes(&settings);
+    let mut remote = remotes.get(&name).cloned().ok_or_else(|| {
+        anyhow::anyhow!(
+            "No remote named {} in {}. Known remotes: {}",
+            name,
+            settings_path().display(),
+            remotes.keys().cloned().collect::<Vec<_>>().join(", ")
+        )
+    })?;
+    if let Some(url) = url.map(str::to_string).or_else(|| std::env::var(ARTIFACTORY_URL_VARIABLE).ok()) {
+        remote.url = url;
+    }
+    remote.url = remote.url.trim_end_matches('/').to_string();
+    Ok((name, remote))
+}
+
+static SELECTED: OnceLock<(String, Remote)> = OnceLock::new();
+
+/// Resolves the remote once at startup, with the command line's `--remote` and `--artifactory-url`.
+pub fn select(name: Option<&str>, url: Option<&str>) -> anyhow::Result<()> {
+    let _ = SELECTED.set(resolve(name, url)?);
+    Ok(())
+}
+
+/// The remote picked at startup. [`select`] has to have succeeded first, so a broken settings file stops the
+/// toolkit instead of quietly sending requests to the built-in remote.
+pub fn selected() -> (String, Remote) {
+    SELECTED.get().cloned().expect("remotes::select is called at startup")
+}
+
+const DEFAULT_REMOTE: &str = "internal";
+const REMOTE_VARIABLE: &str = "SGT_REMOTE";
+const ARTIFACTORY_URL_VARIABLE: &str = "SGT_ARTIFACTORY_URL";
+const SETTINGS_VARIABLE: &str = "SGT_SETTINGS";
+const SETTINGS_FILE_NAME: &str = "settings.json";
+const DEFAULT_URL: &str = "https://artifactory.it.keysight.com/artifactory";
+
+#[cfg(test)]
+mod tests {
+    use crate::remotes::{remotes, Remote, Settings};
+
+    #[test]
+    fn partial_remotes() {
+        let settings: Settings =
+            serde_json::from_str(r#"{"remotes": {"mirror": {"url": "http://mirror:8081/artifactory", "reports": "local/reports"}}}"#)
+                .unwrap();
+        let remotes = remotes(&settings);
+        assert_eq!(remotes.keys().collect::<Vec<_>>(), ["internal", "mirror"]);
+        let mirror = &remotes["mirror"];
+        assert_eq!(mirror.reports, "local/reports");
+        assert_eq!(mirror.packages, Remote::default().packages);
+    }
+}
@@ -1,89 +1,585 @@
-use crate::{hwconfig, logging, versions};
+use crate::common::in_cwd;
+use crate::config_resolution::{self, ConfigFile};
+use crate::redaction::{RedactionSummary, Redactor};
+use crate::report_encryption::{self, Encryption, ENCRYPTED_EXTENSION};
+use crate::report_format::{ArchiveFormat, ArchiveWriter};
+use crate::report_profile::{format_size, Collector, ReportProfile};
+use crate::{hwconfig, logging, report_archive, system_info, versions};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::fmt::Write as fmtWrite;
-use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use strum::IntoEnumIterator;
 
-pub fn create_report(name: &str) -> anyhow::Result<()> {
-    let file_name = zip_file_name(name);
-    let file = std::fs::File::create(&file_name).unwrap();
-    let mut zip = zip::ZipWriter::new(file);
-    zip.add_directory("config/", Default::default())?;
+/// Describes a report zip. Tools reading reports should use this rather than parsing summary.txt.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Manifest {
+    pub manifest_version: u32,
+    pub report_name: String,
+    pub toolkit_version: String,
+    /// RFC 3339, local time.
+    pub generated: String,
+    pub host_name: Option<String>,
+    pub installed_version: Option<String>,
+    pub files: Vec<ManifestFile>,
+    #[serde(default)]
+    pub skipped: Vec<SkippedFile>,
+    /// Present if the report was redacted.
+    #[serde(default)]
+    pub redaction: Option<RedactionSummary>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManifestFile {
+    pub collector: Collector,
+    pub original_path: PathBuf,
+    /// Unique within the archive.
+    pub archive_path: String,
+    /// Size and hash of the archived content, which differs from the original if redacted.
+    pub size: u64,
+    pub modified: Option<String>,
+    pub sha256: String,
+    #[serde(default)]
+    pub redactions: usize,
+    /// Bytes left out from the start of the original to fit the size limits.
+    #[serde(default)]
+    pub truncated: u64,
+}
+
+/// A file a collector found but left out because of the profile's size limits.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SkippedFile {
+    pub collector: Collector,
+    pub original_path: PathBuf,
+    pub size: u64,
+    pub reason: String,
+}
+
+/// Accumulates the zip, summary and manifest while collectors run.
+struct ReportWriter<'a> {
+    archive: ArchiveWriter,
+    summary: String,
+    manifest: Manifest,
+    profile: &'a ReportProfile,
+    total_size: u64,
+    log_size: u64,
+    redactor: Option<Redactor>,
+}
 
-    let mut summary = format!("Report Name: {}\n", name);
+/// Writes the report to `path` in the profile's archive format, encrypted if `encryption` is given. The
+/// unencrypted archive is staged in a temporary file only the user can read, which is removed when done.
+pub fn create_report(path: &Path, name: &str, profile: &ReportProfile, encryption: Option<&Encryption>) -> anyhow::Result<()> {
+    let encryption = match encryption {
+        Some(encryption) => encryption,
+        None => return write_report(path, name, profile),
+    };
+    let staging = tempfile::Builder::new().prefix("sgt-report-").tempfile()?;
+    write_report(staging.path(), name, profile)?;
+    report_encryption::encrypt_file(staging.path(), path, encryption)
+}
+
+/// Creates the report in the working directory, encrypted if the profile or the site policy asks for it, and
+/// returns its path.
+pub fn create_default_report(name: &str, profile: &ReportProfile) -> anyhow::Result<PathBuf> {
+    let encryption = report_encryption::for_report(profile, None)?;
+    let mut path = output_path(name, profile.format, None);
+    if encryption.is_some() {
+        path = report_encryption::encrypted_path(&path);
+    }
+    create_report(&path, name, profile, encryption.as_ref())?;
+    Ok(path)
+}
+
+fn write_report(path: &Path, name: &str, profile: &ReportProfile) -> anyhow::Result<()> {
+    let host_name = gethostname::gethostname().to_str().map(|x| x.to_string());
+    let redactor = match profile.redact {
+        true => Some(Redactor::new(host_name.as_deref(), &profile.redaction_rules)?),
+        false => None,
+    };
 
     const VERSION: &str = env!("CARGO_PKG_VERSION");
-    writeln!(summary, "Toolkit Version: {}", VERSION)?;
+    let mut report = ReportWriter {
+        archive: ArchiveWriter::create(path, profile.format, profile.level)?,
+        summary: format!("Report Name: {}\n", name),
+        manifest: Manifest {
+            manifest_version: MANIFEST_VERSION,
+            report_name: name.to_string(),
+            toolkit_version: VERSION.to_string(),
+            generated: chrono::offset::Local::now().to_rfc3339(),
+            ..Default::default()
+        },
+        profile,
+        total_size: 0,
+        log_size: 0,
+        redactor,
+    };
+    report.archive.add_directory("config/")?;
+    writeln!(report.summary, "Toolkit Version: {}", VERSION)?;
 
-    if let Some(version) = versions::installed_version() {
-        writeln!(summary, "Installed SigGen Version: {}", version)?;
-        zip.start_file("version.txt", Default::default())?;
-        zip.write_all(version.as_bytes())?;
+    if profile.enabled(Collector::Version) {
+        if let Some(version) = versions::installed_version() {
+            writeln!(report.summary, "Installed SigGen Version: {}", version)?;
+            report.add_text("version.txt", &version)?;
+            report.manifest.installed_version = Some(version);
+        }
     }
 
-    if let Some(hostname) = gethostname::gethostname().to_str() {
-        writeln!(summary, "Host Name: {}", hostname)?;
-        zip.start_file("hostname.txt", Default::default())?;
-        zip.write_all(hostname.as_bytes())?;
+    if profile.enabled(Collector::HostName) {
+        if let Some(hostname) = host_name {
+            writeln!(report.summary, "Host Name: {}", hostname)?;
+            report.add_text("hostname.txt", &hostname)?;
+            report.manifest.host_name = Some(report.redact(&hostname));
+        }
     }
 
-    let path = logging::get_log_path_from_current_config();
-    if path.exists() {
-        writeln!(summary, "Log File Path: {}", path.display())?;
-        add_file(&mut zip, path)?;
+    if profile.enabled(Collector::SystemInfo) {
+        let info = system_info::collect();
+        for line in info.summary() {
+            writeln!(report.summary, "{}", line)?;
+        }
+        report.add_text(SYSTEM_INFO_FILE_NAME, &serde_json::to_string_pretty(&info)?)?;
+    }
+
+    if profile.enabled(Collector::Log) {
+        for path in logging::get_log_file_paths(profile.log_days()) {
+            if report.add_file(path.clone(), Collector::Log)? {
+                writeln!(report.summary, "Log File Path: {}", path.display())?;
+            }
+        }
     }
 
     let path = logging::get_exception_log_path();
-    if path.exists() {
-        writeln!(summary, "Exception Log File Path: {}", path.display())?;
-        add_file(&mut zip, path)?;
+    if profile.enabled(Collector::ExceptionLog) && path.exists() {
+        writeln!(report.summary, "Exception Log File Path: {}", path.display())?;
+        report.add_file(path, Collector::ExceptionLog)?;
     }
 
-    if let Some(path) = logging::get_config_path() {
-        writeln!(summary, "Log Config Path: {}", path.display())?;
-        add_file(&mut zip, path)?;
+    if profile.enabled(Collector::LogConfig) {
+        if let Some(path) = logging::get_config_path() {
+            writeln!(report.summary, "Log Config Path: {}", path.display())?;
+            report.add_file(path, Collector::LogConfig)?;
+        }
+        write_shadowed(&mut report.summary, "Shadowed Log Config Paths", ConfigFile::Logging)?;
     }
 
     let path = get_no_reset_system_settings_path();
-    if path.exists() {
-        writeln!(summary, "No Reset System Settings Path: {}", path.display())?;
-        add_file(&mut zip, path)?;
+    if profile.enabled(Collector::NoResetSystemSettings) && path.exists() {
+        writeln!(report.summary, "No Reset System Settings Path: {}", path.display())?;
+        report.add_file(path, Collector::NoResetSystemSettings)?;
     }
 
     let paths = get_data_dir_state_file_paths();
-    if !paths.is_empty() {
-        writeln!(summary, "Data Directory State Files: {}", paths.join(", "))?;
+    if profile.enabled(Collector::StateFiles) && !paths.is_empty() {
+        writeln!(report.summary, "Data Directory State Files: {}", paths.join(", "))?;
+        for path in paths {
+            report.add_file(PathBuf::from(path), Collector::StateFiles)?;
+        }
+    }
+
+    if profile.enabled(Collector::HwConfig) {
+        if let Some(path) = hwconfig::get_path() {
+            writeln!(report.summary, "Hw Config Path: {}", path.display())?;
+            report.add_file(path, Collector::HwConfig)?;
+        }
+        write_shadowed(&mut report.summary, "Shadowed Hw Config Paths", ConfigFile::HwConfig)?;
+    }
+
+    let paths = profile.extra_paths();
+    if profile.enabled(Collector::Extra) && !paths.is_empty() {
+        writeln!(report.summary, "Extra Files: {}", join_paths(&paths))?;
         for path in paths {
-            add_file(&mut zip, PathBuf::from(path))?;
+            report.add_file(path, Collector::Extra)?;
+        }
+    }
+
+    if !profile.exclude.is_empty() {
+        let excluded: Vec<String> = profile.exclude.iter().map(|x| x.to_string()).collect();
+        writeln!(report.summary, "Excluded Collectors: {}", excluded.join(", "))?;
+    }
+    let truncated: Vec<String> = report
+        .manifest
+        .files
+        .iter()
+        .filter(|x| x.truncated > 0)
+        .map(|x| format!("{} (left out the first {} bytes)", x.archive_path, x.truncated))
+        .collect();
+    if !truncated.is_empty() {
+        writeln!(report.summary, "Truncated Files: {}", truncated.join(", "))?;
+    }
+    if !report.manifest.skipped.is_empty() {
+        let skipped: Vec<String> = report
+            .manifest
+            .skipped
+            .iter()
+            .map(|x| format!("{} ({})", x.original_path.display(), x.reason))
+            .collect();
+        writeln!(report.summary, "Skipped Files: {}", skipped.join(", "))?;
+    }
+
+    // Redacted before the counts are recorded. The summary and manifest are then written as they are, so the
+    // counts stay right and a rule can't break the manifest's JSON.
+    let summary = std::mem::take(&mut report.summary);
+    let mut summary = report.redact(&summary);
+    if let Some(redactor) = &report.redactor {
+        let rules = redactor.summary().rules.join(", ");
+        writeln!(summary, "Redacted: {}", rules)?;
+    }
+    report.add_raw_text(SUMMARY_FILE_NAME, &summary)?;
+    report.manifest.redaction = report.redactor.as_ref().map(|x| x.summary().clone());
+    let manifest = serde_json::to_string_pretty(&report.manifest)?;
+    report.add_raw_text(MANIFEST_FILE_NAME, &manifest)?;
+    report.archive.finish()
+}
+
+impl ReportWriter<'_> {
+    fn add_text(&mut self, archive_path: &str, text: &str) -> anyhow::Result<()> {
+        let text = self.redact(text);
+        self.add_raw_text(archive_path, &text)
+    }
+
+    fn add_raw_text(&mut self, archive_path: &str, text: &str) -> anyhow::Result<()> {
+        self.archive.start_file(archive_path)?;
+        self.archive.write_all(text.as_bytes())?;
+        Ok(())
+    }
+
+    fn redact(&mut self, text: &str) -> String {
+        match &mut self.redactor {
+            Some(redactor) => redactor.redact(text).0,
+            None => text.to_string(),
+        }
+    }
+
+    fn redact_path(&mut self, path: PathBuf) -> PathBuf {
+        match &mut self.redactor {
+            Some(redactor) => PathBuf::from(redactor.redact(&path.to_string_lossy()).0),
+            None => path,
+        }
+    }
+
+    fn skip(&mut self, path: PathBuf, collector: Collector, size: u64, reason: String) {
+        let original_path = self.redact_path(path);
+        self.manifest.skipped.push(SkippedFile {
+            collector,
+            original_path,
+            size,
+            reason,
+        });
+    }
+
+    /// The tightest size limit that applies to the next file of a collector, with the reason to give if it's
+    /// exceeded.
+    fn limit(&self, collector: Collector) -> Option<(u64, String)> {
+        let mut limits = vec![];
+        if let Some(max) = self.profile.max_file_size {
+            limits.push((max, format!("larger than {}", format_size(max))));
+        }
+        if let Some(max) = self.profile.max_total_size {
+            limits.push((max.saturating_sub(self.total_size), format!("report would exceed {}", format_size(max))));
+        }
+        if collector == Collector::Log {
+            let max = self.profile.max_log_size();
+            limits.push((max.saturating_sub(self.log_size), format!("log files would exceed {}", format_size(max))));
+        }
+        limits.into_iter().min_by_key(|(limit, _)| *limit)
+    }
+
+    /// Adds the file under `config/`, or `extra/` for extra files, streaming it into the zip. A log that breaks
+    /// a size limit keeps its most recent records, other files are skipped. Returns whether the file was added.
+    fn add_file(&mut self, path: PathBuf, collector: Collector) -> anyhow::Result<bool> {
+        // Logs can be rotated away between finding and reading them, which shouldn't cost the whole report.
+        let (metadata, mut file) = match std::fs::File::open(&path).and_then(|file| Ok((file.metadata()?, file))) {
+            Ok(opened) => opened,
+            Err(err) => {
+                self.skip(path, collector, 0, format!("unreadable: {}", err));
+                return Ok(false);
+            }
+        };
+        // Logs may still be written to, so stop at the size they had when we started.
+        let size = metadata.len();
+        let is_log = matches!(collector, Collector::Log | Collector::ExceptionLog);
+        let (start, marker) = match self.limit(collector) {
+            Some((limit, reason)) if size > limit && is_log && limit >= MIN_TAIL_SIZE => {
+                let start = tail_start(&mut file, size - (limit - TRUNCATION_MARKER_RESERVE), size)?;
+                let marker = format!(
+                    "[sgt report] --- the first {} bytes of {} were left out, {} ---\n",
+                    start, size, reason
+                );
+                (start, Some(marker))
+            }
+            Some((limit, reason)) if size > limit => {
+                self.skip(path, collector, size, reason);
+                return Ok(false);
+            }
+            _ => (0, None),
+        };
+
+        let dir = if collector == Collector::Extra { "extra" } else { "config" };
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let name = self.redact(&name);
+        let archive_path = unique_archive_path(&self.manifest, dir, &name);
+        file.seek(SeekFrom::Start(start))?;
+        let reader = BufReader::new((&mut file).take(size - start));
+        let (written, sha256, redactions) = self.write_entry(&archive_path, marker.as_deref(), reader)?;
+        self.total_size += written;
+        if collector == Collector::Log {
+            self.log_size += written;
+        }
+
+        let modified = metadata
+            .modified()
+            .ok()
+            .map(|x| chrono::DateTime::<chrono::Local>::from(x).to_rfc3339());
+        let original_path = self.redact_path(std::fs::canonicalize(&path).unwrap_or(path));
+        self.manifest.files.push(ManifestFile {
+            collector,
+            original_path,
+            archive_path,
+            size: written,
+            modified,
+            sha256,
+            redactions,
+            truncated: start,
+        });
+        Ok(true)
+    }
+
+    /// Copies `reader` into a new zip entry, after `header` if there is one, returning the size and SHA-256 of
+    /// what was written and the number of redactions. Text is redacted a line at a time, so user rules can't
+    /// match across lines; files that don't start with text are copied as is.
+    fn write_entry(
+        &mut self,
+        archive_path: &str,
+        header: Option<&str>,
+        mut reader: impl BufRead,
+    ) -> anyhow::Result<(u64, String, usize)> {
+        self.archive.start_file(archive_path)?;
+        let mut entry = EntryWriter {
+            archive: &mut self.archive,
+            sha256: Sha256::new(),
+            size: 0,
+        };
+        if let Some(header) = header {
+            entry.write_all(header.as_bytes())?;
         }
+        let mut redactions = 0;
+        match &mut self.redactor {
+            Some(redactor) if is_text(reader.fill_buf()?) => {
+                let mut line = vec![];
+                while reader.read_until(b'\n', &mut line)? > 0 {
+                    let (bytes, count) = redactor.redact_bytes(std::mem::take(&mut line));
+                    entry.write_all(&bytes)?;
+                    redactions += count;
+                }
+            }
+            _ => {
+                std::io::copy(&mut reader, &mut entry)?;
+            }
+        }
+        Ok((entry.size, format!("{:x}", entry.sha256.finalize()), redactions))
     }
+}
 
-    if let Some(path) = hwconfig::get_path() {
-        writeln!(summary, "Hw Config Path: {}", path.display())?;
-        add_file(&mut zip, path)?;
+/// Hashes and counts what is written to an archive entry.
+struct EntryWriter<'a> {
+    archive: &'a mut ArchiveWriter,
+    sha256: Sha256,
+    size: u64,
+}
+
+impl Write for EntryWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.archive.write(buf)?;
+        self.sha256.update(&buf[..written]);
+        self.size += written as u64;
+        Ok(written)
     }
 
-    zip.start_file("summary.txt", Default::default())?;
-    zip.write_all(summary.as_bytes())?;
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.archive.flush()
+    }
+}
 
+/// Valid UTF-8 without NULs, allowing for a character cut off at the end of the buffer.
+fn is_text(bytes: &[u8]) -> bool {
+    let valid = match std::str::from_utf8(bytes) {
+        Ok(_) => true,
+        Err(err) => err.error_len().is_none(),
+    };
+    valid && !bytes.contains(&0)
+}
+
+/// How far to skip from an arbitrary position to the start of the next log record: past the rest of the current
+/// line, then past continuation lines such as stack traces until a line starting with `[`. Gives up looking for
+/// a `[` after a while, in case the log has no such records.
+fn record_start(reader: &mut impl BufRead) -> std::io::Result<u64> {
+    let mut skipped = 0;
+    let mut line = vec![];
+    skipped += reader.read_until(b'\n', &mut line)? as u64;
+    while skipped < MAX_RECORD_SEARCH {
+        match reader.fill_buf()?.first() {
+            None | Some(b'[') => break,
+            Some(_) => {
+                line.clear();
+                skipped += reader.read_until(b'\n', &mut line)? as u64;
+            }
+        }
+    }
+    Ok(skipped)
+}
+
+/// Where the kept tail of a log that was `size` bytes long starts: the first record at or after `from`. What was
+/// appended since the size was taken isn't searched, so this never passes `size`.
+fn tail_start(file: &mut std::fs::File, from: u64, size: u64) -> std::io::Result<u64> {
+    file.seek(SeekFrom::Start(from))?;
+    let skipped = record_start(&mut BufReader::new(Read::take(&mut *file, size - from)))?;
+    Ok((from + skipped).min(size))
+}
+
+fn join_paths(paths: &[PathBuf]) -> String {
+    paths.iter().map(|x| x.display().to_string()).collect::<Vec<String>>().join(", ")
+}
+
+/// Files that exist but are ignored by SigGen are a common source of confusion, so call them out.
+fn write_shadowed(summary: &mut String, label: &str, file: ConfigFile) -> anyhow::Result<()> {
+    let shadowed: Vec<String> = config_resolution::resolve(file)
+        .shadowed()
+        .iter()
+        .map(|candidate| candidate.path.display().to_string())
+        .collect();
+    if !shadowed.is_empty() {
+        writeln!(summary, "{}: {}", label, shadowed.join(", "))?;
+    }
     Ok(())
 }
 
-pub fn zip_file_name(name: &str) -> String {
+pub fn report_file_name(name: &str, format: ArchiveFormat) -> String {
     format!(
-        "{}_{}.zip",
+        "{}_{}{}",
         chrono::offset::Local::now().format("%Y-%m-%d"),
         name.replace(char::is_whitespace, "_").to_lowercase(),
+        format.extension(),
     )
 }
 
-fn add_file(zip: &mut zip::ZipWriter<std::fs::File>, path: PathBuf) -> anyhow::Result<()> {
-    let name = path.file_name().unwrap().to_string_lossy();
-    zip.start_file(format!("{}/{}", "config", name), Default::default())?;
-    let mut f = std::fs::File::open(path)?;
-    let mut buffer = Vec::new();
-    f.read_to_end(&mut buffer)?;
-    zip.write_all(&*buffer)?;
-    Ok(())
+/// Where a report goes: `output` if it names a file, the report's file name in `output` if it is a directory, or
+/// in the working directory without `output`.
+pub fn output_path(name: &str, format: ArchiveFormat, output: Option<&Path>) -> PathBuf {
+    let file_name = report_file_name(name, format);
+    match output {
+        None => in_cwd(file_name),
+        Some(dir) if dir.is_dir() || dir.to_string_lossy().ends_with(['/', '\\']) => dir.join(file_name),
+        Some(path) => path.to_path_buf(),
+    }
+}
+
+/// `path`, or `stem_2.ext`, `stem_3.ext`... if it already exists. `.tar.gz`, `.tar.zst` and an encrypted archive's
+/// extensions count as one.
+pub fn unique_file_path(path: PathBuf) -> PathBuf {
+    if !path.exists() {
+        return path;
+    }
+    let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+    let extension = report_extensions()
+        .into_iter()
+        .filter(|x| file_name.ends_with(x.as_str()))
+        .max_by_key(|x| x.len())
+        .or_else(|| file_name.rfind('.').filter(|i| *i > 0).map(|i| file_name[i..].to_string()))
+        .unwrap_or_default();
+    let stem = &file_name[..file_name.len() - extension.len()];
+    (2..)
+        .map(|n| path.with_file_name(format!("{}_{}{}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap()
+}
+
+/// Extensions of reports in any archive format, encrypted or not.
+pub fn report_extensions() -> Vec<String> {
+    ArchiveFormat::iter()
+        .flat_map(|x| [x.extension(), format!("{}{}", x.extension(), ENCRYPTED_EXTENSION)])
+        .collect()
+}
+
+/// A report in the working directory, which is where the backend creates them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StoredReport {
+    pub name: String,
+    pub size: u64,
+    /// RFC 3339, local time.
+    pub modified: String,
+}
+
+/// Reports in the working directory, newest first. Other archives there, like SigGen packages, are left out.
+pub fn stored_reports() -> anyhow::Result<Vec<StoredReport>> {
+    let extensions = report_extensions();
+    let mut reports = vec![];
+    for entry in std::fs::read_dir(in_cwd(""))?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let metadata = match entry.metadata() {
+            Ok(metadata) if metadata.is_file() => metadata,
+            _ => continue,
+        };
+        if !extensions.iter().any(|x| name.ends_with(x.as_str())) || !is_report(&entry.path()) {
+            continue;
+        }
+        reports.push(StoredReport {
+            name,
+            size: metadata.len(),
+            modified: metadata
+                .modified()
+                .map(|x| chrono::DateTime::<chrono::Local>::from(x).to_rfc3339())
+                .unwrap_or_default(),
+        });
+    }
+    reports.sort_by(|a, b| b.modified.cmp(&a.modified));
+    Ok(reports)
+}
+
+/// The path of the stored report `name`, which has to be a report's file name so that clients can't reach
+/// anything else through it.
+pub fn stored_report_path(name: &str) -> anyhow::Result<PathBuf> {
+    let valid = Path::new(name).file_name().map(|x| x == name).unwrap_or(false)
+        && report_extensions().iter().any(|x| name.ends_with(x.as_str()));
+    if !valid {
+        return Err(anyhow::anyhow!("{} is not a report file name", name));
+    }
+    let path = in_cwd(name);
+    if !path.is_file() || !is_report(&path) {
+        return Err(anyhow::anyhow!("No report named {}", name));
+    }
+    Ok(path)
+}
+
+/// An archive with a report manifest, or an age encrypted file, which only reports are. Tar reports are read to
+/// the end, as the manifest comes last.
+fn is_report(path: &Path) -> bool {
+    if report_encryption::is_encrypted(path).unwrap_or(false) {
+        return true;
+    }
+    report_archive::inspect(path).is_ok_and(|info| info.manifest.is_some())
+}
+
+/// Removes the stored report `name`.
+pub fn delete_stored_report(name: &str) -> anyhow::Result<()> {
+    Ok(std::fs::remove_file(stored_report_path(name)?)?)
+}
+
+/// `dir/name`, or `dir/stem_2.ext`, `dir/stem_3.ext`... if an earlier file already took it.
+fn unique_archive_path(manifest: &Manifest, dir: &str, name: &str) -> String {
+    let taken = |candidate: &str| manifest.files.iter().any(|file| file.archive_path == candidate);
+    let first = format!("{}/{}", dir, name);
+    if !taken(&first) {
+        return first;
+    }
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (name, String::new()),
+    };
+    (2..)
+        .map(|n| format!("{}/{}_{}{}", dir, stem, n, extension))
+        .find(|candidate| !taken(candidate))
+        .unwrap()
 }
 
 pub fn get_no_reset_system_settings_path() -> PathBuf {
@@ -91,22 +587,72 @@ pub fn get_no_reset_system_settings_path() -> PathBuf {
         .join("Keysight")
         .join("PathWave")
         .join("SignalGenerator")
-        .join("SigGenInstrumentSpecificSettings.sgen")
+        .join(NO_RESET_SYSTEM_SETTINGS_FILE_NAME)
+}
+
+/// Where SigGen keeps its `.sgen` state files.
+pub fn get_data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("Keysight").join("PathWave").join("SignalGenerator"))
 }
 
 pub fn get_data_dir_state_file_paths() -> Vec<String> {
-    dirs::data_dir()
-        .and_then(|dir| {
-            glob::glob(
-                dir.join("Keysight")
-                    .join("PathWave")
-                    .join("SignalGenerator")
-                    .join("*.sgen")
-                    .to_string_lossy()
-                    .as_ref(),
-            )
-            .ok()
-        })
+    get_data_dir()
+        .and_then(|dir| glob::glob(dir.join("*.sgen").to_string_lossy().as_ref()).ok())
         .map(|glob| glob.flatten().map(|path| path.to_string_lossy().to_string()).collect())
         .unwrap_or_default()
 }
+
+pub const NO_RESET_SYSTEM_SETTINGS_FILE_NAME: &str = "SigGenInstrumentSpecificSettings.sgen";
+/// Logs aren't truncated to less than this, they are skipped instead.
+const MIN_TAIL_SIZE: u64 = 4 << 10;
+/// Room left for the truncation marker within a size limit.
+const TRUNCATION_MARKER_RESERVE: u64 = 256;
+const MAX_RECORD_SEARCH: u64 = 64 << 10;
+pub const SYSTEM_INFO_FILE_NAME: &str = "system.json";
+pub const SUMMARY_FILE_NAME: &str = "summary.txt";
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+
+#[cfg(test)]
+mod tests {
+    use crate::report::{record_start, tail_start, unique_archive_path, Manifest, ManifestFile};
+    use crate::report_profile::Collector;
+    use std::io::{Seek, SeekFrom, Write};
+
+    #[test]
+    fn unique_archive_paths() {
+        let mut manifest = Manifest::default();
+        for expected in ["config/state.sgen", "config/state_2.sgen", "config/state_3.sgen"] {
+            let archive_path = unique_archive_path(&manifest, "config", "state.sgen");
+            assert_eq!(archive_path, expected);
+            manifest.files.push(ManifestFile {
+                collector: Collector::StateFiles,
+                original_path: Default::default(),
+                archive_path,
+                size: 0,
+                modified: None,
+                sha256: String::new(),
+                redactions: 0,
+                truncated: 0,
+            });
+        }
+        assert_eq!(unique_archive_path(&manifest, "config", "hostname"), "config/hostname");
+    }
+
+    #[test]
+    fn record_starts() {
+        let log = "ial line\n  at Stack.Trace()\n[12:00:01] [Sghal] [error] next\n";
+        assert_eq!(record_start(&mut log.as_bytes()).unwrap(), 28);
+        assert_eq!(record_start(&mut "no records\nat all".as_bytes()).unwrap(), 17);
+    }
+
+    #[test]
+    fn tail_start_of_growing_log() {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(b"partial\n[12:00:00] first\ncut off").unwrap();
+        let size = file.seek(SeekFrom::End(0)).unwrap();
+        file.write_all(b" line\n[12:00:01] appended\n").unwrap();
+        assert_eq!(tail_start(&mut file, 0, size).unwrap(), 8);
+        assert_eq!(tail_start(&mut file, 10, size).unwrap(), size);
+    }
+}
@@ -0,0 +1,418 @@
+use crate::common::*;
+use crate::report::{self, Manifest, ManifestFile, MANIFEST_FILE_NAME, SUMMARY_FILE_NAME};
+use crate::report_encryption::ENCRYPTED_EXTENSION;
+use crate::report_format::{self, ArchiveFormat};
+use crate::report_profile::Collector;
+use crate::{hwconfig, logging};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use strum::{Display, EnumIter, EnumString};
+
+/// What a report contains. Reports created before manifests were added have no manifest.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ReportInfo {
+    pub manifest: Option<Manifest>,
+    pub summary: Option<String>,
+    pub entries: Vec<ArchiveEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub size: u64,
+}
+
+impl ReportInfo {
+    /// From the manifest if there is one, otherwise guessed from the file name.
+    pub fn collector_of(&self, name: &str) -> Option<Collector> {
+        match &self.manifest {
+            Some(_) => self.manifest_file(name).map(|file| file.collector),
+            None => match name.rsplit('/').next() {
+                Some(logging::FILE_NAME) => Some(Collector::LogConfig),
+                Some(hwconfig::FILE_NAME) => Some(Collector::HwConfig),
+                Some(report::NO_RESET_SYSTEM_SETTINGS_FILE_NAME) => Some(Collector::NoResetSystemSettings),
+                Some(file_name) if file_name.ends_with(".sgen") => Some(Collector::StateFiles),
+                Some(file_name) if file_name.ends_with(".log") => Some(Collector::Log),
+                _ => None,
+            },
+        }
+    }
+
+    pub fn redactions_of(&self, name: &str) -> usize {
+        self.manifest_file(name).map_or(0, |file| file.redactions)
+    }
+
+    /// The name the file had where it was collected. Archive names get a suffix when they clash.
+    pub fn original_file_name(&self, name: &str) -> Option<String> {
+        match self.manifest_file(name) {
+            Some(file) => file.original_path.file_name().map(|x| x.to_string_lossy().to_string()),
+            None => name.rsplit('/').next().map(|x| x.to_string()),
+        }
+    }
+
+    fn manifest_file(&self, name: &str) -> Option<&ManifestFile> {
+        self.manifest
+            .iter()
+            .flat_map(|manifest| &manifest.files)
+            .find(|file| file.archive_path == name)
+    }
+
+    /// Archive paths of the files a collector contributed, in archive order.
+    pub fn files_of(&self, collector: Collector) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|entry| self.collector_of(&entry.name) == Some(collector))
+            .map(|entry| entry.name.clone())
+            .collect()
+    }
+
+    /// Accepts an archive path, or a file name if only one entry has it.
+    pub fn resolve(&self, name: &str) -> anyhow::Result<String> {
+        if self.entries.iter().any(|entry| entry.name == name) {
+            return Ok(name.to_string());
+        }
+        let matches: Vec<&ArchiveEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.name.rsplit('/').next() == Some(name))
+            .collect();
+        match matches.as_slice() {
+            [entry] => Ok(entry.name.clone()),
+            [] => Err(anyhow::anyhow!("No file named {} in report", name)),
+            _ => Err(anyhow::anyhow!(
+                "{} is ambiguous: {}",
+                name,
+                matches.iter().map(|x| x.name.as_str()).collect::<Vec<&str>>().join(", ")
+            )),
+        }
+    }
+}
+
+pub fn inspect(path: &Path) -> anyhow::Result<ReportInfo> {
+    let mut info = ReportInfo::default();
+    for_each_entry(path, |entry, reader| {
+        match entry.name.as_str() {
+            MANIFEST_FILE_NAME => info.manifest = serde_json::from_slice(&read_to_end(reader)?).ok(),
+            SUMMARY_FILE_NAME => info.summary = Some(String::from_utf8_lossy(&read_to_end(reader)?).to_string()),
+            _ => {}
+        }
+        info.entries.push(entry.clone());
+        Ok(())
+    })?;
+    Ok(info)
+}
+
+/// `name` is an archive path, or a file name if it is unique in the report.
+pub fn read_file(path: &Path, name: &str) -> anyhow::Result<Vec<u8>> {
+    let name = inspect(path)?.resolve(name)?;
+    let mut bytes = None;
+    for_each_entry(path, |entry, reader| {
+        if bytes.is_none() && entry.name == name {
+            bytes = Some(read_to_end(reader)?);
+        }
+        Ok(())
+    })?;
+    bytes.ok_or_else(|| anyhow::anyhow!("No file named {} in report", name))
+}
+
+/// Calls `visit` with each file and a reader of its contents, in archive order. Zip entries the visitor doesn't
+/// read are never decompressed. Tar reports are read from start to end.
+pub fn for_each_entry(
+    path: &Path,
+    mut visit: impl FnMut(&ArchiveEntry, &mut dyn Read) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    if path.to_string_lossy().ends_with(ENCRYPTED_EXTENSION) {
+        return Err(anyhow::anyhow!("{} is encrypted, decrypt it with 'sgt report decrypt' first", path.display()));
+    }
+    match ArchiveFormat::of(path) {
+        Some(format @ (ArchiveFormat::TarGz | ArchiveFormat::TarZst)) => {
+            let mut archive = report_format::tar_reader(path, format)?;
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                if entry.header().entry_type().is_file() {
+                    let archive_entry = ArchiveEntry {
+                        name: String::from_utf8_lossy(&entry.path_bytes()).to_string(),
+                        size: entry.size(),
+                    };
+                    visit(&archive_entry, &mut entry)?;
+                }
+            }
+        }
+        _ => {
+            let file = std::fs::File::open(path).map_err(|err| anyhow::anyhow!("{}: {}", path.display(), err))?;
+            let mut archive = zip::ZipArchive::new(file)?;
+            for i in 0..archive.len() {
+                let mut file = archive.by_index(i)?;
+                if !file.is_dir() {
+                    let entry = ArchiveEntry {
+                        name: file.name().to_string(),
+                        size: file.size(),
+                    };
+                    visit(&entry, &mut file)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn read_text(path: &Path, name: &str) -> anyhow::Result<String> {
+    Ok(String::from_utf8_lossy(&read_file(path, name)?).to_string())
+}
+
+/// Extracts every file below `dir`, returning the paths written. Entries escaping `dir` are rejected.
+pub fn extract(path: &Path, dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut written = vec![];
+    for_each_entry(path, |entry, reader| {
+        let relative = enclosed_name(&entry.name)
+            .ok_or_else(|| anyhow::anyhow!("Unsafe path in report: {}", entry.name))?;
+        let path = dir.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::io::copy(reader, &mut std::fs::File::create(&path)?)?;
+        written.push(path);
+        Ok(())
+    })?;
+    Ok(written)
+}
+
+/// `None` for absolute names and ones with `..`, which could point outside the extraction directory.
+fn enclosed_name(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    let normal = path.components().all(|x| matches!(x, Component::Normal(_) | Component::CurDir));
+    (normal && !name.is_empty()).then(|| path.to_path_buf())
+}
+
+fn read_to_end(reader: &mut dyn Read) -> anyhow::Result<Vec<u8>> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Groups of files `apply` can put back in place.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, EnumString, EnumIter, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum ApplyTarget {
+    Logging,
+    Hwconfig,
+    State,
+}
+
+impl ApplyTarget {
+    fn of(collector: Collector) -> Option<Self> {
+        match collector {
+            Collector::LogConfig => Some(Self::Logging),
+            Collector::HwConfig => Some(Self::Hwconfig),
+            Collector::StateFiles | Collector::NoResetSystemSettings => Some(Self::State),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Created,
+    Replaced { backup: PathBuf },
+    Unchanged,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Applied {
+    pub archive_path: String,
+    pub destination: PathBuf,
+    pub outcome: Outcome,
+    /// Redactions in the archived file, which would end up in the destination.
+    pub redactions: usize,
+}
+
+impl fmt::Display for Applied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.outcome {
+            Outcome::Created => write!(f, "create    {} <- {}", self.destination.display(), self.archive_path)?,
+            Outcome::Replaced { backup } => write!(
+                f,
+                "replace   {} <- {} (backup {})",
+                self.destination.display(),
+                self.archive_path,
+                backup.display()
+            )?,
+            Outcome::Unchanged => write!(f, "unchanged {}", self.destination.display())?,
+        }
+        if self.redactions > 0 {
+            write!(f, " ⚠ {} redactions", self.redactions)?;
+        }
+        Ok(())
+    }
+}
+
+/// Copies the configuration and state files in a report to where SigGen reads them on this machine. Files
+/// that would be overwritten are first copied to a timestamped directory in the toolkit's backups. An empty
+/// `only` applies every target. Files with redactions are refused unless `force` is set, since placeholders like
+/// `<host-name>` would end up in live configuration. With `dry_run`, returns what would be done without touching
+/// anything.
+pub fn apply(path: &Path, only: &[ApplyTarget], dry_run: bool, force: bool) -> anyhow::Result<Vec<Applied>> {
+    let info = inspect(path)?;
+    let backup_dir = toolkit_dir()
+        .join("backups")
+        .join(chrono::Local::now().format("%Y-%m-%d_%H-%M-%S").to_string());
+    let mut applied = vec![];
+    let mut contents = vec![];
+    for_each_entry(path, |entry, reader| {
+        let target = info.collector_of(&entry.name).and_then(|collector| {
+            ApplyTarget::of(collector).filter(|target| only.is_empty() || only.contains(target))?;
+            destination(collector, &info.original_file_name(&entry.name)?)
+        });
+        let destination = match target {
+            Some(destination) => destination,
+            None => return Ok(()),
+        };
+        let entry_contents = read_to_end(reader)?;
+        let outcome = match std::fs::read(&destination) {
+            Ok(existing) if existing == entry_contents => Outcome::Unchanged,
+            Ok(_) => Outcome::Replaced {
+                backup: unique_backup_path(&applied, &backup_dir, &destination),
+            },
+            Err(_) => Outcome::Created,
+        };
+        applied.push(Applied {
+            archive_path: entry.name.clone(),
+            destination,
+            outcome,
+            redactions: info.redactions_of(&entry.name),
+        });
+        contents.push(entry_contents);
+        Ok(())
+    })?;
+    if dry_run {
+        return Ok(applied);
+    }
+
+    let redacted: Vec<String> = applied
+        .iter()
+        .filter(|x| x.redactions > 0 && x.outcome != Outcome::Unchanged)
+        .map(|x| x.archive_path.clone())
+        .collect();
+    if !force && !redacted.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Refusing to apply redacted files: {}\n\
+             Their placeholders would replace real values. Consider --only or the --force flag.",
+            redacted.join(", ")
+        ));
+    }
+    for (applied, contents) in applied.iter().zip(contents) {
+        let destination = &applied.destination;
+        if let Outcome::Replaced { backup } = &applied.outcome {
+            std::fs::create_dir_all(&backup_dir)?;
+            std::fs::copy(destination, backup)?;
+        }
+        if applied.outcome != Outcome::Unchanged {
+            std::fs::create_dir_all(destination.parent().unwrap())?;
+            std::fs::write(destination, &contents)
+                .map_err(|err| anyhow::anyhow!("{}: {}", destination.display(), err))?;
+        }
+    }
+    Ok(applied)
+}
+
+/// `dir/name`, or `dir/stem_2.ext`, `dir/stem_3.ext`... if an earlier file's backup already took it.
+fn unique_backup_path(applied: &[Applied], dir: &Path, destination: &Path) -> PathBuf {
+    let taken = |candidate: &Path| {
+        applied
+            .iter()
+            .any(|x| matches!(&x.outcome, Outcome::Replaced { backup } if backup == candidate))
+    };
+    let first = dir.join(destination.file_name().unwrap());
+    if !taken(&first) {
+        return first;
+    }
+    let stem = destination.file_stem().unwrap().to_string_lossy();
+    let extension = destination
+        .extension()
+        .map(|x| format!(".{}", x.to_string_lossy()))
+        .unwrap_or_default();
+    (2..)
+        .map(|n| dir.join(format!("{}_{}{}", stem, n, extension)))
+        .find(|candidate| !taken(candidate))
+        .unwrap()
+}
+
+fn destination(collector: Collector, file_name: &str) -> Option<PathBuf> {
+    match collector {
+        Collector::LogConfig => Some(logging::get_config_path_or_cwd()),
+        Collector::HwConfig => Some(hwconfig::get_path_or_cwd()),
+        Collector::NoResetSystemSettings => Some(report::get_no_reset_system_settings_path()),
+        Collector::StateFiles => report::get_data_dir().map(|dir| dir.join(file_name)),
+        _ => None,
+    }
+}
+
+/// `reports/2021-01-01_name.zip` extracts to `reports/2021-01-01_name`, as does `2021-01-01_name.tar.gz`.
+pub fn default_extract_dir(path: &Path) -> PathBuf {
+    match ArchiveFormat::of(path) {
+        Some(format) => {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            path.with_file_name(&name[..name.len() - format.extension().len()])
+        }
+        None => path.with_extension(""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::report_archive::{
+        default_extract_dir, enclosed_name, unique_backup_path, Applied, ArchiveEntry, Outcome, ReportInfo,
+    };
+    use crate::report_profile::Collector;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn resolve_names() {
+        let info = ReportInfo {
+            entries: ["config/ksflogger.cfg", "config/a.sgen", "extra/a.sgen", "config/sg.log"]
+                .iter()
+                .map(|name| ArchiveEntry {
+                    name: name.to_string(),
+                    size: 0,
+                })
+                .collect(),
+            ..Default::default()
+        };
+        assert_eq!(info.resolve("ksflogger.cfg").unwrap(), "config/ksflogger.cfg");
+        assert_eq!(info.resolve("extra/a.sgen").unwrap(), "extra/a.sgen");
+        assert!(info.resolve("a.sgen").is_err());
+        assert!(info.resolve("missing").is_err());
+        assert_eq!(info.files_of(Collector::Log), vec!["config/sg.log"]);
+    }
+
+    #[test]
+    fn unique_backup_paths() {
+        let dir = Path::new("backups");
+        let mut applied = vec![];
+        for (destination, expected) in [
+            ("a/state.sgen", "backups/state.sgen"),
+            ("b/state.sgen", "backups/state_2.sgen"),
+            ("c/state.sgen", "backups/state_3.sgen"),
+            ("c/hostname", "backups/hostname"),
+        ] {
+            let backup = unique_backup_path(&applied, dir, Path::new(destination));
+            assert_eq!(backup, PathBuf::from(expected));
+            applied.push(Applied {
+                archive_path: String::new(),
+                destination: PathBuf::from(destination),
+                outcome: Outcome::Replaced { backup },
+                redactions: 0,
+            });
+        }
+    }
+
+    #[test]
+    fn extraction_paths() {
+        assert_eq!(enclosed_name("config/a.sgen"), Some(PathBuf::from("config/a.sgen")));
+        assert_eq!(enclosed_name("../a.sgen"), None);
+        assert_eq!(enclosed_name("config/../../a.sgen"), None);
+        assert_eq!(enclosed_name("/etc/passwd"), None);
+        assert_eq!(default_extract_dir(Path::new("r/2021-01-01_a.tar.gz")), PathBuf::from("r/2021-01-01_a"));
+        assert_eq!(default_extract_dir(Path::new("r/2021-01-01_a.zip")), PathBuf::from("r/2021-01-01_a"));
+    }
+}
@@ -0,0 +1,330 @@
+use crate::hwconfig_diff::{self, Change, Diff, Mode};
+use crate::report::{MANIFEST_FILE_NAME, SUMMARY_FILE_NAME, SYSTEM_INFO_FILE_NAME};
+use crate::report_archive::{self, ReportInfo};
+use crate::report_profile::{format_size, Collector};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// Differences between an older report `a` and a newer report `b`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Comparison {
+    /// Versions and host name.
+    pub fields: Vec<Change>,
+    /// None if neither report has a hardware configuration.
+    pub hwconfig: Option<Diff>,
+    pub logging: Vec<Change>,
+    /// Values are the size and the start of the SHA-256 of each collected file. Files the report writes about
+    /// itself, such as the summary and system information, are left out.
+    pub files: Vec<Change>,
+    /// Only from logs that differ between the reports.
+    pub log_stats: Vec<LogStat>,
+}
+
+/// Warnings, errors or critical messages a logger wrote in each report's logs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LogStat {
+    pub logger: String,
+    pub level: String,
+    pub old: usize,
+    pub new: usize,
+}
+
+/// What is read from each side.
+struct Side {
+    fields: Vec<(String, String)>,
+    hwconfig: Option<String>,
+    logging: Option<String>,
+    log_stats: BTreeMap<(String, String), usize>,
+}
+
+/// Archive path, size and SHA-256 of each collected file.
+type Hashes = Vec<(String, u64, String)>;
+
+/// Files are compared by the hashes in the manifests. Only the configurations and the logs that differ are read.
+pub fn compare(a: &Path, b: &Path) -> anyhow::Result<Comparison> {
+    let old_info = report_archive::inspect(a)?;
+    let new_info = report_archive::inspect(b)?;
+    let old_hashes = hashes(a, &old_info)?;
+    let new_hashes = hashes(b, &new_info)?;
+    let old = read_side(a, &old_info, &old_hashes, &new_hashes)?;
+    let new = read_side(b, &new_info, &new_hashes, &old_hashes)?;
+    let hwconfig = match (&old.hwconfig, &new.hwconfig) {
+        (None, None) => None,
+        (old, new) => Some(hwconfig_diff::diff(
+            old.as_deref().unwrap_or_default(),
+            new.as_deref().unwrap_or_default(),
+            Mode::Auto,
+        )),
+    };
+    let mut log_stats: BTreeMap<(String, String), LogStat> = BTreeMap::new();
+    for ((logger, level), count) in &old.log_stats {
+        log_stats.entry((logger.clone(), level.clone())).or_insert_with(|| LogStat::new(logger, level)).old = *count;
+    }
+    for ((logger, level), count) in &new.log_stats {
+        log_stats.entry((logger.clone(), level.clone())).or_insert_with(|| LogStat::new(logger, level)).new = *count;
+    }
+    Ok(Comparison {
+        fields: hwconfig_diff::diff_values(&old.fields, &new.fields),
+        hwconfig,
+        logging: diff_logging(old.logging.as_deref(), new.logging.as_deref()),
+        files: hwconfig_diff::diff_values(&file_values(&old_hashes), &file_values(&new_hashes)),
+        log_stats: log_stats.into_values().collect(),
+    })
+}
+
+impl LogStat {
+    fn new(logger: &str, level: &str) -> Self {
+        Self {
+            logger: logger.to_string(),
+            level: level.to_string(),
+            old: 0,
+            new: 0,
+        }
+    }
+}
+
+/// From the manifest, or by hashing each entry for reports created before manifests were added.
+fn hashes(zip_path: &Path, info: &ReportInfo) -> anyhow::Result<Hashes> {
+    if let Some(manifest) = &info.manifest {
+        return Ok(manifest
+            .files
+            .iter()
+            .map(|file| (file.archive_path.clone(), file.size, file.sha256.clone()))
+            .collect());
+    }
+    let mut hashes = vec![];
+    report_archive::for_each_entry(zip_path, |entry, reader| {
+        let name = entry.name.as_str();
+        if !GENERATED_FILES.contains(&name) {
+            let mut hasher = Sha256::new();
+            let size = std::io::copy(reader, &mut hasher)?;
+            hashes.push((name.to_string(), size, format!("{:x}", hasher.finalize())));
+        }
+        Ok(())
+    })?;
+    Ok(hashes)
+}
+
+fn file_values(hashes: &Hashes) -> Vec<(String, String)> {
+    hashes
+        .iter()
+        .map(|(name, size, sha256)| {
+            let short = &sha256[..12.min(sha256.len())];
+            (name.clone(), format!("{} {}", format_size(*size), short))
+        })
+        .collect()
+}
+
+fn read_side(zip_path: &Path, info: &ReportInfo, hashes: &Hashes, other: &Hashes) -> anyhow::Result<Side> {
+    let hash_of = |hashes: &Hashes, name: &str| hashes.iter().find(|x| x.0 == name).map(|x| x.2.clone());
+    let hwconfig = info.files_of(Collector::HwConfig).into_iter().next();
+    let logging = info.files_of(Collector::LogConfig).into_iter().next();
+    let mut side = Side {
+        fields: fields(info),
+        hwconfig: None,
+        logging: None,
+        log_stats: BTreeMap::new(),
+    };
+    report_archive::for_each_entry(zip_path, |entry, reader| {
+        let name = entry.name.as_str();
+        let text = |reader: &mut dyn Read| -> anyhow::Result<String> {
+            let mut bytes = vec![];
+            reader.read_to_end(&mut bytes)?;
+            Ok(String::from_utf8_lossy(&bytes).to_string())
+        };
+        if hwconfig.as_deref() == Some(name) {
+            side.hwconfig = Some(text(reader)?);
+        } else if logging.as_deref() == Some(name) {
+            side.logging = Some(text(reader)?);
+        } else if info.collector_of(name) == Some(Collector::Log) && hash_of(hashes, name) != hash_of(other, name) {
+            count_messages(BufReader::new(reader), &mut side.log_stats)?;
+        }
+        Ok(())
+    })?;
+    Ok(side)
+}
+
+/// From the manifest, or summary.txt for reports created before manifests were added.
+fn fields(info: &ReportInfo) -> Vec<(String, String)> {
+    let values = match &info.manifest {
+        Some(manifest) => vec![
+            Some(manifest.toolkit_version.clone()),
+            manifest.installed_version.clone(),
+            manifest.host_name.clone(),
+        ],
+        None => {
+            let summary = info.summary.as_deref().unwrap_or_default();
+            let find = |label: &str| {
+                summary
+                    .lines()
+                    .find_map(|line| line.strip_prefix(label)?.strip_prefix(": ").map(|x| x.to_string()))
+            };
+            vec![find("Toolkit Version"), find("Installed SigGen Version"), find("Host Name")]
+        }
+    };
+    ["Toolkit Version", "SigGen Version", "Host Name"]
+        .iter()
+        .zip(values)
+        .filter_map(|(label, value)| Some((label.to_string(), value?)))
+        .collect()
+}
+
+/// Sinks and loggers are matched by name, a logger's sinks are compared as a set. Falls back to comparing lines
+/// if either side isn't valid JSON.
+fn diff_logging(old: Option<&str>, new: Option<&str>) -> Vec<Change> {
+    let parse = |text: Option<&str>| match text {
+        None => Ok(Value::Null),
+        Some(text) => serde_json::from_str::<Value>(text),
+    };
+    match (parse(old), parse(new)) {
+        (Ok(old_value), Ok(new_value)) => {
+            let mut old = vec![];
+            let mut new = vec![];
+            flatten_json("", &old_value, &mut old);
+            flatten_json("", &new_value, &mut new);
+            hwconfig_diff::diff_values(&old, &new)
+        }
+        _ => {
+            hwconfig_diff::diff(old.unwrap_or_default(), new.unwrap_or_default(), Mode::Lines).changes
+        }
+    }
+}
+
+fn flatten_json(address: &str, value: &Value, values: &mut Vec<(String, String)>) {
+    let join = |key: &str| match address {
+        "" => key.to_string(),
+        _ => format!("{}.{}", address, key),
+    };
+    match value {
+        Value::Null => {}
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten_json(&join(key), value, values);
+            }
+        }
+        Value::Array(items) if items.iter().all(|x| x.get("name").and_then(Value::as_str).is_some()) => {
+            for item in items {
+                let name = item["name"].as_str().unwrap();
+                flatten_json(&format!("{}[{}]", address, name), item, values);
+            }
+        }
+        Value::Array(items) => {
+            let mut items: Vec<String> = items.iter().map(scalar).collect();
+            items.sort();
+            values.push((address.to_string(), items.join(", ")));
+        }
+        value => values.push((address.to_string(), scalar(value))),
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Counts spdlog lines such as `[2021-03-01 12:00:00.000] [Sghal] [error] ...` at warning level and above. Lines
+/// without a logger name are counted under `default`.
+fn count_messages(mut reader: impl BufRead, counts: &mut BTreeMap<(String, String), usize>) -> anyhow::Result<()> {
+    let mut line = vec![];
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        if let Some(captures) = LOG_LINE.captures(&String::from_utf8_lossy(&line)) {
+            let logger = captures.get(1).map_or("default", |x| x.as_str());
+            let level = match &captures[2] {
+                "warn" => "warning",
+                "err" => "error",
+                level => level,
+            };
+            *counts.entry((logger.to_string(), level.to_string())).or_default() += 1;
+        }
+        line.clear();
+    }
+    Ok(())
+}
+
+/// Written by the toolkit rather than collected, in current and older reports.
+const GENERATED_FILES: [&str; 5] = [
+    MANIFEST_FILE_NAME,
+    SUMMARY_FILE_NAME,
+    SYSTEM_INFO_FILE_NAME,
+    "hostname.txt",
+    "version.txt",
+];
+
+lazy_static! {
+    static ref LOG_LINE: Regex =
+        Regex::new(r"^\[[^\]]*\]\s+(?:\[([^\]]+)\]\s+)?\[(warning|warn|error|err|critical)\]").unwrap();
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_changes(f, "Summary", &self.fields)?;
+        match &self.hwconfig {
+            Some(diff) => write_changes(f, &format!("Hardware Configuration ({})", diff.mode), &diff.changes)?,
+            None => writeln!(f, "Hardware Configuration\n  not in either report")?,
+        }
+        write_changes(f, "Logging Configuration", &self.logging)?;
+        write_changes(f, "Files", &self.files)?;
+        writeln!(f, "Log Messages")?;
+        if self.log_stats.is_empty() {
+            writeln!(f, "  no warnings or errors")?;
+        }
+        for stat in &self.log_stats {
+            let marker = if stat.new > stat.old { "▲" } else if stat.new < stat.old { "▼" } else { " " };
+            writeln!(f, "  {} {:24} {:8} {:>6} -> {}", marker, stat.logger, stat.level, stat.old, stat.new)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_changes(f: &mut fmt::Formatter<'_>, heading: &str, changes: &[Change]) -> fmt::Result {
+    writeln!(f, "{}", heading)?;
+    if changes.is_empty() {
+        writeln!(f, "  no differences")?;
+    }
+    for change in changes {
+        writeln!(f, "  {}", change)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::report_compare::{count_messages, diff_logging};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn logging_by_name() {
+        let old = r#"{"sinks": [{"type": "console", "name": "a", "level": "info"}, {"type": "file", "name": "b", "level": "info", "file_name": "x.log"}],
+                      "loggers": [{"name": "Sghal", "level": "info", "sinks": ["a", "b"]}]}"#;
+        let new = r#"{"sinks": [{"type": "file", "name": "b", "level": "debug", "file_name": "x.log"}, {"type": "console", "name": "a", "level": "info"}],
+                      "loggers": [{"name": "Sghal", "level": "info", "sinks": ["b", "a"]}]}"#;
+        let changes: Vec<String> = diff_logging(Some(old), Some(new)).iter().map(|x| x.to_string()).collect();
+        assert_eq!(changes, vec!["~ sinks[b].level: info -> debug"]);
+    }
+
+    #[test]
+    fn log_messages() {
+        let mut counts = BTreeMap::new();
+        count_messages(
+            "[2021-03-01 12:00:00.000] [Sghal] [error] failed\n\
+             [2021-03-01 12:00:00.001] [Sghal] [info] fine\n\
+             [2021-03-01 12:00:00.002] [warning] no logger\n\
+             [2021-03-01 12:00:00.003] [Sghal] [err] failed again"
+                .as_bytes(),
+            &mut counts,
+        )
+        .unwrap();
+        assert_eq!(counts.get(&("Sghal".to_string(), "error".to_string())), Some(&2));
+        assert_eq!(counts.get(&("default".to_string(), "warning".to_string())), Some(&1));
+        assert_eq!(counts.len(), 2);
+    }
+}
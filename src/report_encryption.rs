@@ -0,0 +1,226 @@
+use crate::common::*;
+use crate::report_profile::ReportProfile;
+use age::secrecy::SecretString;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Machine-wide rules for reports, set by the site's administrators.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct SitePolicy {
+    /// Unencrypted reports are not uploaded.
+    pub require_encryption: bool,
+    /// Reports are encrypted to these when neither the profile nor the command line says otherwise.
+    pub recipients: Vec<String>,
+}
+
+pub fn site_policy_path() -> PathBuf {
+    if cfg!(windows) {
+        PathBuf::from(std::env::var("ProgramData").unwrap_or_else(|_| r"C:\ProgramData".to_string()))
+            .join("SigGenToolkit")
+            .join(SITE_POLICY_FILE_NAME)
+    } else {
+        PathBuf::from("/etc/sgt").join(SITE_POLICY_FILE_NAME)
+    }
+}
+
+/// A missing file is the default policy, which requires nothing.
+pub fn load_site_policy() -> anyhow::Result<SitePolicy> {
+    let path = site_policy_path();
+    if !path.exists() {
+        return Ok(SitePolicy::default());
+    }
+    let text = std::fs::read_to_string(&path)?;
+    serde_json::from_str(&text).map_err(|err| anyhow::anyhow!("{}: {}", path.display(), err))
+}
+
+pub enum Encryption {
+    /// age public keys (`age1...`) or files listing them, one per line.
+    Recipients(Vec<String>),
+    Passphrase(SecretString),
+}
+
+/// How a report is encrypted: with the passphrase if given, otherwise to the profile's recipients, otherwise to
+/// the site policy's. `None` if none of them ask for it.
+pub fn for_report(profile: &ReportProfile, passphrase: Option<SecretString>) -> anyhow::Result<Option<Encryption>> {
+    if let Some(passphrase) = passphrase {
+        return Ok(Some(Encryption::Passphrase(passphrase)));
+    }
+    if !profile.encrypt_to.is_empty() {
+        return Ok(Some(Encryption::Recipients(profile.encrypt_to.clone())));
+    }
+    let policy = load_site_policy()?;
+    Ok((!policy.recipients.is_empty()).then_some(Encryption::Recipients(policy.recipients)))
+}
+
+/// Reads the passphrase from `SGT_REPORT_PASSPHRASE`, or asks for it on the terminal. Empty passphrases are
+/// refused either way.
+pub fn read_passphrase(confirm: bool) -> anyhow::Result<SecretString> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_VARIABLE) {
+        if passphrase.is_empty() {
+            return Err(anyhow::anyhow!("{} is empty", PASSPHRASE_VARIABLE));
+        }
+        return Ok(SecretString::from(passphrase));
+    }
+    let passphrase = rpassword::prompt_password("Passphrase: ")?;
+    if passphrase.is_empty() {
+        return Err(anyhow::anyhow!("The passphrase is empty"));
+    }
+    if confirm && rpassword::prompt_password("Confirm passphrase: ")? != passphrase {
+        return Err(anyhow::anyhow!("The passphrases don't match"));
+    }
+    Ok(SecretString::from(passphrase))
+}
+
+/// `path` with the encrypted file extension added.
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}{}", path.display(), ENCRYPTED_EXTENSION))
+}
+
+/// Whether the file starts with the age header.
+pub fn is_encrypted(path: &Path) -> anyhow::Result<bool> {
+    let mut header = [0u8; AGE_HEADER.len()];
+    let mut file = File::open(path).map_err(|err| anyhow::anyhow!("{}: {}", path.display(), err))?;
+    Ok(file.read_exact(&mut header).is_ok() && header == *AGE_HEADER)
+}
+
+/// Writes `input` encrypted to `output`. The output is removed again if anything goes wrong.
+pub fn encrypt_file(input: &Path, output: &Path, encryption: &Encryption) -> anyhow::Result<()> {
+    let encryptor = match encryption {
+        Encryption::Passphrase(passphrase) => age::Encryptor::with_user_passphrase(passphrase.clone()),
+        Encryption::Recipients(recipients) => {
+            let recipients = parse_recipients(recipients)?;
+            age::Encryptor::with_recipients(recipients.iter().map(|x| x as &dyn age::Recipient))?
+        }
+    };
+    let result = (|| -> anyhow::Result<()> {
+        let mut reader = File::open(input)?;
+        let mut writer = encryptor.wrap_output(File::create(output)?)?;
+        std::io::copy(&mut reader, &mut writer)?;
+        writer.finish()?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(output);
+    }
+    result
+}
+
+/// Decrypts `input` with the passphrase or with the identities, the private keys matching the recipients it was
+/// encrypted to. Without identities, the default identity file is used.
+pub fn decrypt_file(
+    input: &Path,
+    output: &Path,
+    identities: &[PathBuf],
+    passphrase: impl FnOnce() -> anyhow::Result<SecretString>,
+) -> anyhow::Result<()> {
+    let file = File::open(input).map_err(|err| anyhow::anyhow!("{}: {}", input.display(), err))?;
+    let decryptor = age::Decryptor::new_buffered(BufReader::new(file))?;
+    let mut reader = if decryptor.is_scrypt() {
+        let identity = age::scrypt::Identity::new(passphrase()?);
+        decryptor.decrypt(std::iter::once(&identity as &dyn age::Identity))?
+    } else {
+        let paths = match identities {
+            [] => vec![default_identity_path()],
+            _ => identities.to_vec(),
+        };
+        let mut keys = vec![];
+        for path in paths {
+            let file = age::IdentityFile::from_file(path.to_string_lossy().to_string())
+                .map_err(|err| anyhow::anyhow!("{}: {}", path.display(), err))?;
+            keys.extend(file.into_identities()?);
+        }
+        decryptor.decrypt(keys.iter().map(|x| x.as_ref()))?
+    };
+    let result = (|| -> anyhow::Result<()> {
+        let mut writer = File::create(output)?;
+        std::io::copy(&mut reader, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(output);
+    }
+    result
+}
+
+/// Where `report decrypt` looks for private keys by default.
+pub fn default_identity_path() -> PathBuf {
+    toolkit_dir().join("report_identity.txt")
+}
+
+/// Refuses reports the site policy doesn't allow to leave the machine.
+pub fn check_upload(path: &Path) -> anyhow::Result<()> {
+    if load_site_policy()?.require_encryption && !is_encrypted(path)? {
+        return Err(anyhow::anyhow!(
+            "{} is not encrypted, which the site policy in {} requires for uploads.\n\
+             Create the report with --encrypt-to or --passphrase.",
+            path.display(),
+            site_policy_path().display()
+        ));
+    }
+    Ok(())
+}
+
+fn parse_recipients(recipients: &[String]) -> anyhow::Result<Vec<age::x25519::Recipient>> {
+    let mut keys = vec![];
+    for recipient in recipients {
+        if recipient.starts_with("age1") {
+            keys.push(parse_recipient(recipient)?);
+            continue;
+        }
+        let text = std::fs::read_to_string(recipient)
+            .map_err(|err| anyhow::anyhow!("{} is neither an age public key nor a readable file: {}", recipient, err))?;
+        for line in text.lines().map(str::trim).filter(|x| !x.is_empty() && !x.starts_with('#')) {
+            keys.push(parse_recipient(line)?);
+        }
+    }
+    if keys.is_empty() {
+        return Err(anyhow::anyhow!("No recipients to encrypt to"));
+    }
+    Ok(keys)
+}
+
+fn parse_recipient(key: &str) -> anyhow::Result<age::x25519::Recipient> {
+    age::x25519::Recipient::from_str(key).map_err(|err| anyhow::anyhow!("Invalid public key {}: {}", key, err))
+}
+
+pub const ENCRYPTED_EXTENSION: &str = ".age";
+pub const PASSPHRASE_VARIABLE: &str = "SGT_REPORT_PASSPHRASE";
+const SITE_POLICY_FILE_NAME: &str = "site_policy.json";
+const AGE_HEADER: &[u8] = b"age-encryption.org/v1";
+
+#[cfg(test)]
+mod tests {
+    use crate::report_encryption::{decrypt_file, encrypt_file, is_encrypted, Encryption};
+    use age::secrecy::ExposeSecret;
+
+    #[test]
+    fn round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let plain = dir.join("report.zip");
+        std::fs::write(&plain, b"report contents").unwrap();
+
+        let identity = age::x25519::Identity::generate();
+        let identity_path = dir.join("identity.txt");
+        std::fs::write(&identity_path, identity.to_string().expose_secret()).unwrap();
+        let encrypted = dir.join("report.zip.age");
+        let recipients = Encryption::Recipients(vec![identity.to_public().to_string()]);
+        encrypt_file(&plain, &encrypted, &recipients).unwrap();
+        assert!(is_encrypted(&encrypted).unwrap() && !is_encrypted(&plain).unwrap());
+
+        let decrypted = dir.join("decrypted.zip");
+        decrypt_file(&encrypted, &decrypted, &[identity_path], || unreachable!()).unwrap();
+        assert_eq!(std::fs::read(&decrypted).unwrap(), b"report contents");
+
+        let other = age::x25519::Identity::generate();
+        let other_path = dir.join("other.txt");
+        std::fs::write(&other_path, other.to_string().expose_secret()).unwrap();
+        assert!(decrypt_file(&encrypted, &dir.join("wrong.zip"), &[other_path], || unreachable!()).is_err());
+        assert!(!dir.join("wrong.zip").exists());
+    }
+}
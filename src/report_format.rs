@@ -0,0 +1,270 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default, EnumString, EnumIter, Display)]
+pub enum ArchiveFormat {
+    #[default]
+    #[serde(rename = "zip")]
+    #[strum(serialize = "zip")]
+    Zip,
+    #[serde(rename = "tar.gz")]
+    #[strum(serialize = "tar.gz")]
+    TarGz,
+    #[serde(rename = "tar.zst")]
+    #[strum(serialize = "tar.zst")]
+    TarZst,
+}
+
+impl ArchiveFormat {
+    pub fn extension(&self) -> String {
+        format!(".{}", self)
+    }
+
+    /// From the file name, ignoring case.
+    pub fn of(path: &Path) -> Option<Self> {
+        let name = path.to_string_lossy().to_ascii_lowercase();
+        ArchiveFormat::iter().find(|x| name.ends_with(&x.extension()))
+    }
+
+    /// Zip and gzip go from 0 to 9, zstd from 1 to 19.
+    fn levels(&self) -> (u32, u32) {
+        match self {
+            ArchiveFormat::Zip | ArchiveFormat::TarGz => (0, 9),
+            ArchiveFormat::TarZst => (1, 19),
+        }
+    }
+}
+
+/// Writes report entries one after another, in any of the archive formats.
+pub enum ArchiveWriter {
+    Zip {
+        zip: zip::ZipWriter<File>,
+        options: zip::write::FileOptions,
+    },
+    Tar(TarWriter<Compressor>),
+}
+
+impl ArchiveWriter {
+    /// `level` is the format's default if not given. The zip writer only distinguishes level 0, which stores
+    /// files uncompressed, from the rest.
+    pub fn create(path: &Path, format: ArchiveFormat, level: Option<u32>) -> anyhow::Result<Self> {
+        let (min, max) = format.levels();
+        if let Some(level) = level.filter(|x| !(min..=max).contains(x)) {
+            return Err(anyhow::anyhow!("Level {} is not in {}..={} for {}", level, min, max, format));
+        }
+        let create = || File::create(path).map_err(|err| anyhow::anyhow!("{}: {}", path.display(), err));
+        Ok(match format {
+            ArchiveFormat::Zip => ArchiveWriter::Zip {
+                zip: zip::ZipWriter::new(create()?),
+                options: zip::write::FileOptions::default().compression_method(match level {
+                    Some(0) => zip::CompressionMethod::Stored,
+                    _ => zip::CompressionMethod::Deflated,
+                }),
+            },
+            ArchiveFormat::TarGz => ArchiveWriter::Tar(TarWriter::new(Compressor::Gzip(flate2::write::GzEncoder::new(
+                create()?,
+                level.map_or_else(flate2::Compression::default, flate2::Compression::new),
+            )))?),
+            ArchiveFormat::TarZst => ArchiveWriter::Tar(TarWriter::new(Compressor::Zstd(zstd::Encoder::new(
+                create()?,
+                level.map_or(zstd::DEFAULT_COMPRESSION_LEVEL, |x| x as i32),
+            )?))?),
+        })
+    }
+
+    pub fn add_directory(&mut self, name: &str) -> anyhow::Result<()> {
+        match self {
+            ArchiveWriter::Zip { zip, options } => zip.add_directory(name, *options)?,
+            ArchiveWriter::Tar(tar) => tar.add_directory(name)?,
+        }
+        Ok(())
+    }
+
+    /// Following writes go to this entry.
+    pub fn start_file(&mut self, name: &str) -> anyhow::Result<()> {
+        match self {
+            ArchiveWriter::Zip { zip, options } => zip.start_file(name, *options)?,
+            ArchiveWriter::Tar(tar) => tar.start_file(name)?,
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> anyhow::Result<()> {
+        match self {
+            ArchiveWriter::Zip { mut zip, .. } => {
+                zip.finish()?;
+            }
+            ArchiveWriter::Tar(tar) => tar.finish()?.finish()?,
+        }
+        Ok(())
+    }
+}
+
+impl Write for ArchiveWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            ArchiveWriter::Zip { zip, .. } => zip.write(buf),
+            ArchiveWriter::Tar(tar) => tar.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            ArchiveWriter::Zip { zip, .. } => zip.flush(),
+            ArchiveWriter::Tar(tar) => tar.flush(),
+        }
+    }
+}
+
+pub enum Compressor {
+    Gzip(flate2::write::GzEncoder<File>),
+    Zstd(zstd::Encoder<'static, File>),
+}
+
+impl Compressor {
+    fn finish(self) -> anyhow::Result<()> {
+        match self {
+            Compressor::Gzip(gzip) => {
+                gzip.finish()?;
+            }
+            Compressor::Zstd(zstd) => {
+                zstd.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Write for Compressor {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Compressor::Gzip(gzip) => gzip.write(buf),
+            Compressor::Zstd(zstd) => zstd.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Compressor::Gzip(gzip) => gzip.flush(),
+            Compressor::Zstd(zstd) => zstd.flush(),
+        }
+    }
+}
+
+/// Reads a tar report's entries in order, decompressing as it goes.
+pub fn tar_reader(path: &Path, format: ArchiveFormat) -> anyhow::Result<tar::Archive<Box<dyn Read>>> {
+    let file = BufReader::new(File::open(path).map_err(|err| anyhow::anyhow!("{}: {}", path.display(), err))?);
+    let reader: Box<dyn Read> = match format {
+        ArchiveFormat::TarGz => Box::new(flate2::read::GzDecoder::new(file)),
+        ArchiveFormat::TarZst => Box::new(zstd::Decoder::with_buffer(file)?),
+        ArchiveFormat::Zip => return Err(anyhow::anyhow!("{} is not a tar report", path.display())),
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+/// Writes entries with [`tar::Builder`]. A tar header holds the entry's size, so entries are spooled to an anonymous
+/// temporary file until the next one starts.
+pub struct TarWriter<W: Write> {
+    builder: tar::Builder<W>,
+    entry: Option<String>,
+    spool: File,
+    mtime: u64,
+}
+
+impl<W: Write> TarWriter<W> {
+    fn new(out: W) -> anyhow::Result<Self> {
+        Ok(Self {
+            builder: tar::Builder::new(out),
+            entry: None,
+            spool: tempfile::tempfile()?,
+            mtime: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |x| x.as_secs()),
+        })
+    }
+
+    fn header(&self, kind: tar::EntryType, size: u64, mode: u32) -> tar::Header {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(kind);
+        header.set_size(size);
+        header.set_mode(mode);
+        header.set_mtime(self.mtime);
+        header
+    }
+
+    fn add_directory(&mut self, name: &str) -> anyhow::Result<()> {
+        self.end_entry()?;
+        let mut header = self.header(tar::EntryType::Directory, 0, 0o755);
+        self.builder.append_data(&mut header, name, std::io::empty())?;
+        Ok(())
+    }
+
+    fn start_file(&mut self, name: &str) -> anyhow::Result<()> {
+        self.end_entry()?;
+        self.spool.set_len(0)?;
+        self.spool.seek(SeekFrom::Start(0))?;
+        self.entry = Some(name.to_string());
+        Ok(())
+    }
+
+    fn end_entry(&mut self) -> anyhow::Result<()> {
+        if let Some(name) = self.entry.take() {
+            let size = self.spool.stream_position()?;
+            let mut header = self.header(tar::EntryType::Regular, size, 0o644);
+            self.spool.seek(SeekFrom::Start(0))?;
+            self.builder.append_data(&mut header, name, (&mut self.spool).take(size))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the end of archive marker and returns the underlying writer.
+    fn finish(mut self) -> anyhow::Result<W> {
+        self.end_entry()?;
+        Ok(self.builder.into_inner()?)
+    }
+}
+
+impl<W: Write> Write for TarWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match &self.entry {
+            Some(_) => self.spool.write(buf),
+            None => Err(std::io::Error::other("No tar entry started")),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.builder.get_mut().flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::report_format::{tar_reader, ArchiveFormat, ArchiveWriter};
+    use std::io::{Read, Write};
+
+    #[test]
+    fn tar_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.tar.gz");
+        let long = format!("extra/{}/{}", "d".repeat(90), "f".repeat(90));
+        let mut archive = ArchiveWriter::create(&path, ArchiveFormat::TarGz, None).unwrap();
+        archive.add_directory("config").unwrap();
+        archive.start_file("config/sghal_dev.cfg").unwrap();
+        archive.write_all(b"slot = 2\n").unwrap();
+        archive.start_file(&long).unwrap();
+        archive.finish().unwrap();
+
+        let mut entries = vec![];
+        for entry in tar_reader(&path, ArchiveFormat::TarGz).unwrap().entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let mut text = String::new();
+            entry.read_to_string(&mut text).unwrap();
+            entries.push((entry.path().unwrap().to_string_lossy().to_string(), text));
+        }
+        let expected = [("config", ""), ("config/sghal_dev.cfg", "slot = 2\n"), (long.as_str(), "")];
+        assert_eq!(entries, expected.map(|(name, text)| (name.to_string(), text.to_string())));
+    }
+}
@@ -0,0 +1,158 @@
+use crate::common::*;
+use crate::redaction::RedactionRule;
+use crate::report_format::ArchiveFormat;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use strum::{Display, EnumIter, EnumString};
+
+/// One source of files for a report. Every collector is enabled unless a profile excludes it.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, EnumString, EnumIter, Display)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum Collector {
+    Version,
+    HostName,
+    /// OS, hardware, disk space and running SigGen processes.
+    SystemInfo,
+    Log,
+    ExceptionLog,
+    LogConfig,
+    NoResetSystemSettings,
+    StateFiles,
+    HwConfig,
+    /// Files matching the profile's extra glob patterns.
+    Extra,
+}
+
+/// Controls what goes into a report. Stored as JSON, by default in the toolkit directory.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ReportProfile {
+    pub exclude: BTreeSet<Collector>,
+    pub extra: Vec<String>,
+    /// Larger files are skipped and listed in the manifest.
+    pub max_file_size: Option<u64>,
+    /// Files that would take the report past this are skipped.
+    pub max_total_size: Option<u64>,
+    /// How many days of daily log files to collect.
+    pub log_days: Option<u32>,
+    /// Older log files are skipped once the newer ones reach this.
+    pub max_log_size: Option<u64>,
+    /// Replace host names, user names, addresses and emails in text files.
+    pub redact: bool,
+    /// Applied after the built-in rules when redacting.
+    pub redaction_rules: Vec<RedactionRule>,
+    pub format: ArchiveFormat,
+    /// Compression level, the format's default if not set.
+    pub level: Option<u32>,
+    /// age public keys, or files listing them, to encrypt the report to.
+    pub encrypt_to: Vec<String>,
+}
+
+impl ReportProfile {
+    pub fn enabled(&self, collector: Collector) -> bool {
+        !self.exclude.contains(&collector)
+    }
+
+    pub fn set_enabled(&mut self, collector: Collector, enabled: bool) {
+        if enabled {
+            self.exclude.remove(&collector);
+        } else {
+            self.exclude.insert(collector);
+        }
+    }
+
+    pub fn log_days(&self) -> u32 {
+        self.log_days.unwrap_or(DEFAULT_LOG_DAYS)
+    }
+
+    pub fn max_log_size(&self) -> u64 {
+        self.max_log_size.unwrap_or(DEFAULT_MAX_LOG_SIZE)
+    }
+
+    /// Files matching the extra patterns, in pattern order, without duplicates.
+    pub fn extra_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = vec![];
+        for pattern in &self.extra {
+            if let Ok(glob) = glob::glob(pattern) {
+                for path in glob.flatten().filter(|x| x.is_file()) {
+                    if !paths.contains(&path) {
+                        paths.push(path);
+                    }
+                }
+            }
+        }
+        paths
+    }
+}
+
+pub fn default_profile_path() -> PathBuf {
+    toolkit_dir().join("report_profile.json")
+}
+
+/// A missing file is an empty profile, i.e. collect everything.
+pub fn load(path: &Path) -> anyhow::Result<ReportProfile> {
+    if !path.exists() {
+        return Ok(Default::default());
+    }
+    let text = std::fs::read_to_string(path)?;
+    serde_json::from_str(&text).map_err(|err| anyhow::anyhow!("{}: {}", path.display(), err))
+}
+
+pub fn save(path: &Path, profile: &ReportProfile) -> anyhow::Result<()> {
+    std::fs::create_dir_all(path.parent().unwrap())?;
+    std::fs::write(path, serde_json::to_string_pretty(profile)?)?;
+    Ok(())
+}
+
+/// Bytes, optionally with a binary `K`, `M` or `G` suffix, e.g. `512K` or `10M`.
+pub fn parse_size(text: &str) -> anyhow::Result<u64> {
+    let upper = text.trim().to_ascii_uppercase();
+    let upper = upper.strip_suffix('B').unwrap_or(&upper);
+    let (number, multiplier) = match upper.char_indices().last() {
+        Some((i, 'K')) => (&upper[..i], 1 << 10),
+        Some((i, 'M')) => (&upper[..i], 1 << 20),
+        Some((i, 'G')) => (&upper[..i], 1 << 30),
+        _ => (upper, 1),
+    };
+    let number = number
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| anyhow::anyhow!("Invalid size: {}", text))?;
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| anyhow::anyhow!("Size too large: {}", text))
+}
+
+/// The exact size in the form [`parse_size`] reads, for showing limits. Use `common::format_bytes` for sizes
+/// that only need to be readable.
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        x if x >= 1 << 30 && x % (1 << 30) == 0 => format!("{}G", x >> 30),
+        x if x >= 1 << 20 && x % (1 << 20) == 0 => format!("{}M", x >> 20),
+        x if x >= 1 << 10 && x % (1 << 10) == 0 => format!("{}K", x >> 10),
+        x => x.to_string(),
+    }
+}
+
+const DEFAULT_LOG_DAYS: u32 = 7;
+const DEFAULT_MAX_LOG_SIZE: u64 = 100 << 20;
+
+#[cfg(test)]
+mod tests {
+    use crate::report_profile::{format_size, parse_size};
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("100").unwrap(), 100);
+        assert_eq!(parse_size("512K").unwrap(), 512 * 1024);
+        assert_eq!(parse_size("10MB").unwrap(), 10 * 1024 * 1024);
+        assert_eq!(parse_size("1g").unwrap(), 1 << 30);
+        assert!(parse_size("ten").is_err());
+        assert!(parse_size("B").is_err());
+        assert!(parse_size("17179869184G").is_err());
+        assert_eq!(format_size(10 * 1024 * 1024), "10M");
+        assert_eq!(format_size(1500), "1500");
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{report_archive, versions};
+use std::fmt;
+use std::path::Path;
+
+/// Artifactory item properties attached to an uploaded report, so reports can be found without downloading them.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UploadProperties {
+    pub host_name: Option<String>,
+    pub siggen_version: Option<String>,
+    pub toolkit_version: Option<String>,
+    pub reporter: Option<String>,
+    pub ticket: Option<String>,
+}
+
+impl UploadProperties {
+    /// From the report's manifest if it has one that can be read, otherwise from this machine. The reporter
+    /// defaults to the logged in user.
+    pub fn for_report(path: &Path, reporter: Option<String>, ticket: Option<String>) -> Self {
+        let manifest = report_archive::inspect(path).ok().and_then(|x| x.manifest);
+        let local = || UploadProperties {
+            host_name: gethostname::gethostname().to_str().map(|x| x.to_string()),
+            siggen_version: versions::installed_version(),
+            toolkit_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            ..Default::default()
+        };
+        let properties = match manifest {
+            Some(manifest) => UploadProperties {
+                host_name: manifest.host_name,
+                siggen_version: manifest.installed_version,
+                toolkit_version: Some(manifest.toolkit_version),
+                ..Default::default()
+            },
+            None => local(),
+        };
+        UploadProperties {
+            reporter: reporter.or_else(|| std::env::var("USERNAME").or_else(|_| std::env::var("USER")).ok()),
+            ticket: ticket.filter(|x| !x.trim().is_empty()),
+            ..properties
+        }
+    }
+
+    /// Property names and values, leaving out the ones without a value.
+    pub fn pairs(&self) -> Vec<(&'static str, String)> {
+        [
+            ("sgt.host", &self.host_name),
+            ("sgt.siggen_version", &self.siggen_version),
+            ("sgt.toolkit_version", &self.toolkit_version),
+            ("sgt.reporter", &self.reporter),
+            ("sgt.ticket", &self.ticket),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.clone().map(|x| (name, x)))
+        .collect()
+    }
+
+    /// Matrix parameters setting the properties when appended to a deploy URL, e.g. `;sgt.ticket=SG-1234`.
+    pub fn matrix_params(&self) -> String {
+        self.pairs()
+            .iter()
+            .map(|(name, value)| format!(";{}={}", name, encode(value)))
+            .collect()
+    }
+}
+
+impl fmt::Display for UploadProperties {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in self.pairs() {
+            writeln!(f, "{:20} {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Percent-encodes everything but unreserved characters, since `;`, `=` and `,` separate matrix parameters.
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|x| match x {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (x as char).to_string(),
+            _ => format!("%{:02X}", x),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::report_upload::UploadProperties;
+
+    #[test]
+    fn matrix_params() {
+        let properties = UploadProperties {
+            host_name: Some("lab-7".to_string()),
+            reporter: Some("j doe".to_string()),
+            ticket: Some("SG-1;2=3".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            properties.matrix_params(),
+            ";sgt.host=lab-7;sgt.reporter=j%20doe;sgt.ticket=SG-1%3B2%3D3"
+        );
+    }
+}
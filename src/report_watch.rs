@@ -0,0 +1,190 @@
+use crate::report_profile::ReportProfile;
+use crate::report_upload::UploadProperties;
+use crate::versions::{self, UploadStatus};
+use crate::{logging, report};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+pub struct WatchOptions {
+    pub interval: Duration,
+    /// Log lines containing any of these trigger a report. Any new exception log content always does.
+    pub patterns: Vec<String>,
+    /// Minimum time between reports. Triggers in between are captured together once it has passed.
+    pub cooldown: Duration,
+    /// How many auto-generated reports to keep in the working directory.
+    pub keep: usize,
+    pub upload: bool,
+}
+
+/// Decides when to create a report so a burst of errors produces one report instead of hundreds.
+#[derive(Default)]
+struct RateLimiter {
+    last: Option<Instant>,
+    pending: bool,
+}
+
+impl RateLimiter {
+    fn should_capture(&mut self, now: Instant, triggered: bool, cooldown: Duration) -> bool {
+        self.pending |= triggered;
+        let ready = self.last.is_none_or(|last| now.duration_since(last) >= cooldown);
+        if self.pending && ready {
+            self.pending = false;
+            self.last = Some(now);
+            return true;
+        }
+        false
+    }
+}
+
+/// Follows the end of a file across truncation and rotation. Only complete lines are returned.
+struct Tail {
+    path: PathBuf,
+    offset: u64,
+}
+
+impl Tail {
+    /// Starts at the current end, so only content written from now on is seen.
+    fn new(path: PathBuf) -> Self {
+        let offset = std::fs::metadata(&path).map(|x| x.len()).unwrap_or(0);
+        Self { path, offset }
+    }
+
+    fn read_lines(&mut self) -> Vec<String> {
+        let mut file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(_) => return vec![],
+        };
+        let len = file.metadata().map(|x| x.len()).unwrap_or(0);
+        if len < self.offset {
+            self.offset = 0;
+        }
+        let mut bytes = vec![];
+        if file.seek(SeekFrom::Start(self.offset)).is_err() || file.read_to_end(&mut bytes).is_err() {
+            return vec![];
+        }
+        let complete = match bytes.iter().rposition(|x| *x == b'\n') {
+            Some(i) => i + 1,
+            None => return vec![],
+        };
+        self.offset += complete as u64;
+        String::from_utf8_lossy(&bytes[..complete])
+            .lines()
+            .map(|x| x.to_string())
+            .collect()
+    }
+}
+
+/// Polls the exception log and the active log sinks until the process is stopped, creating a report whenever
+/// a new exception is logged or a line matches one of the patterns.
+pub fn watch(options: &WatchOptions, profile: &ReportProfile) -> anyhow::Result<()> {
+    let mut exception_log = Tail::new(logging::get_exception_log_path());
+    let mut logs: HashMap<PathBuf, Tail> = HashMap::new();
+    let mut limiter = RateLimiter::default();
+    println!("Watching {}", exception_log.path.display());
+    loop {
+        // Daily sinks move to a new file at midnight and the configuration may change, so look again each time.
+        for path in logging::get_active_log_file_paths() {
+            if !logs.contains_key(&path) {
+                println!("Watching {}", path.display());
+                logs.insert(path.clone(), Tail::new(path));
+            }
+        }
+
+        let mut reasons = vec![];
+        if !exception_log.read_lines().is_empty() {
+            reasons.push("new exception".to_string());
+        }
+        for tail in logs.values_mut() {
+            for line in tail.read_lines() {
+                if let Some(pattern) = options.patterns.iter().find(|x| line.contains(x.as_str())) {
+                    reasons.push(format!("'{}' in {}", pattern, tail.path.display()));
+                }
+            }
+        }
+        for reason in reasons.iter() {
+            println!("{} {}", chrono::Local::now().format("%H:%M:%S"), reason);
+        }
+
+        if limiter.should_capture(Instant::now(), !reasons.is_empty(), options.cooldown) {
+            if let Err(err) = capture(options, profile) {
+                println!("Error creating report: {}", err);
+            }
+        } else if limiter.pending && !reasons.is_empty() {
+            println!("Waiting for the cooldown before creating a report");
+        }
+        std::thread::sleep(options.interval);
+    }
+}
+
+fn capture(options: &WatchOptions, profile: &ReportProfile) -> anyhow::Result<()> {
+    let name = format!("{}{}", AUTO_PREFIX, chrono::Local::now().format("%H%M%S"));
+    let path = report::create_default_report(&name, profile)?;
+    println!("Created {}", path.display());
+    if options.upload {
+        let properties = UploadProperties::for_report(&path, None, None);
+        let status = Arc::new(Mutex::new(UploadStatus::Idle));
+        match versions::VersionsClient::default().upload_report(&path, &properties, status, None)?.join() {
+            Ok(Ok(_)) => println!("Uploaded {}", path.display()),
+            Ok(Err(err)) => println!("Error uploading {}: {}", path.display(), err),
+            Err(_) => println!("Error uploading {}", path.display()),
+        }
+    }
+    for path in expired(auto_reports(), options.keep) {
+        match std::fs::remove_file(&path) {
+            Ok(_) => println!("Removed {}", path.display()),
+            Err(err) => println!("Error removing {}: {}", path.display(), err),
+        }
+    }
+    Ok(())
+}
+
+/// In any archive format, encrypted or not, since the profile may have changed between runs.
+fn auto_reports() -> Vec<(PathBuf, SystemTime)> {
+    report::report_extensions()
+        .into_iter()
+        .filter_map(|extension| glob::glob(&format!("*_{}*{}", AUTO_PREFIX, extension)).ok())
+        .flat_map(|paths| paths.flatten())
+        .filter_map(|path| {
+            let modified = std::fs::metadata(&path).and_then(|x| x.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
+
+/// All but the `keep` newest.
+fn expired(mut reports: Vec<(PathBuf, SystemTime)>, keep: usize) -> Vec<PathBuf> {
+    reports.sort_by_key(|x| std::cmp::Reverse(x.1));
+    reports.into_iter().skip(keep).map(|(path, _)| path).collect()
+}
+
+/// Auto-generated reports are named `<date>_auto-<time>` plus the format's extension.
+const AUTO_PREFIX: &str = "auto-";
+
+#[cfg(test)]
+mod tests {
+    use crate::report_watch::{expired, RateLimiter};
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant, SystemTime};
+
+    #[test]
+    fn rate_limit_and_retention() {
+        let cooldown = Duration::from_secs(600);
+        let start = Instant::now();
+        let mut limiter = RateLimiter::default();
+        assert!(!limiter.should_capture(start, false, cooldown));
+        assert!(limiter.should_capture(start, true, cooldown));
+        assert!(!limiter.should_capture(start + Duration::from_secs(60), true, cooldown));
+        assert!(!limiter.should_capture(start + Duration::from_secs(120), false, cooldown));
+        assert!(limiter.should_capture(start + cooldown, false, cooldown));
+        assert!(!limiter.should_capture(start + cooldown * 3, false, cooldown));
+
+        let now = SystemTime::now();
+        let reports = (0..4)
+            .map(|i| (PathBuf::from(format!("{}.zip", i)), now - Duration::from_secs(i * 60)))
+            .collect();
+        assert_eq!(expired(reports, 2), vec![PathBuf::from("2.zip"), PathBuf::from("3.zip")]);
+    }
+}
@@ -2,14 +2,27 @@ use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use crate::{ion_diagnostics, logging, report, common, hwconfig, versions};
 use rocket::{serde::json::Json, get, post, launch, http::Status, delete};
+use crate::config_resolution::{self, ConfigFile, Resolution};
+use crate::hwconfig::Snapshot;
+use crate::hwconfig_diff;
+use crate::hwconfig_profiles::{self, Profile};
+use crate::ion_catalog::Catalog;
 use crate::ion_diagnostics::DiagnosticsConfiguration;
 use crate::logging::{LoggingConfiguration, Template};
+use crate::report_archive::{self, ReportInfo};
+use crate::report_format::ArchiveFormat;
+use crate::report_profile::{self, ReportProfile};
 
 #[get("/cwd", format = "json")]
 fn get_cwd() -> Json<PathBuf> {
     Json(common::in_cwd(PathBuf::new()))
 }
 
+#[get("/config-resolution/<file>", format = "json")]
+fn get_config_resolution(file: &str) -> Option<Json<Resolution>> {
+    ConfigFile::from_str(file).ok().map(|x| Json(config_resolution::resolve(x)))
+}
+
 #[get("/logging/config-path", format = "json")]
 fn get_logging_config_path() -> Json<Option<PathBuf>> {
     Json(logging::get_config_path())
@@ -30,9 +43,9 @@ fn get_logging_config(path: PathBuf) -> Option<Json<LoggingConfiguration>> {
     logging::get_config_from(&Path::new("/").join(path)).map(|config| Json(config))
 }
 
-#[get("/logging/log-path-from-current-config", format = "json")]
-fn get_logging_log_path_from_current_config() -> Json<PathBuf> {
-    Json(logging::get_log_path_from_current_config())
+#[get("/logging/log-files/<days>", format = "json")]
+fn get_logging_log_files(days: u32) -> Json<Vec<PathBuf>> {
+    Json(logging::get_log_file_paths(days))
 }
 
 #[post("/logging/config/<path..>", format = "json", data = "<config>")]
@@ -61,9 +74,50 @@ fn set_ion_diagnostics_config(path: PathBuf, config: Json<DiagnosticsConfigurati
     }
 }
 
-#[post("/reports/create/<name>")]
-fn create_report(name: &str) -> Status {
-    match report::create_report(name) {
+#[get("/ion-diagnostics/debug-dir", format = "json")]
+fn get_ion_diagnostics_debug_dir() -> Json<Option<PathBuf>> {
+    Json(ion_diagnostics::get_debug_dir())
+}
+
+#[get("/ion-diagnostics/catalog", format = "json")]
+fn get_ion_diagnostics_catalog() -> Json<Catalog> {
+    Json(Catalog::refresh(&[]))
+}
+
+#[post("/reports/create/<name>", data = "<profile>")]
+fn create_report(name: &str, profile: Json<ReportProfile>) -> Result<Json<PathBuf>, (Status, String)> {
+    report::create_default_report(name, &profile)
+        .map(Json)
+        .map_err(|err| (Status::InternalServerError, err.to_string()))
+}
+
+#[get("/reports/stored", format = "json")]
+fn get_stored_reports() -> Result<Json<Vec<report::StoredReport>>, (Status, String)> {
+    report::stored_reports()
+        .map(Json)
+        .map_err(|err| (Status::InternalServerError, err.to_string()))
+}
+
+#[get("/reports/stored/<name>")]
+fn get_stored_report(name: &str) -> Result<std::fs::File, (Status, String)> {
+    report::stored_report_path(name)
+        .and_then(|path| Ok(std::fs::File::open(path)?))
+        .map_err(|err| (Status::NotFound, err.to_string()))
+}
+
+#[delete("/reports/stored/<name>")]
+fn delete_stored_report(name: &str) -> Result<(), (Status, String)> {
+    report::delete_stored_report(name).map_err(|err| (Status::NotFound, err.to_string()))
+}
+
+#[get("/reports/profile", format = "json")]
+fn get_report_profile() -> Json<ReportProfile> {
+    Json(report_profile::load(&report_profile::default_profile_path()).unwrap_or_default())
+}
+
+#[post("/reports/profile", data = "<profile>")]
+fn set_report_profile(profile: Json<ReportProfile>) -> Status {
+    match report_profile::save(&report_profile::default_profile_path(), &profile) {
         Ok(_) => {Status::Ok}
         Err(_) => {Status::InternalServerError}
     }
@@ -79,20 +133,26 @@ fn get_exception_log_path() -> Json<PathBuf> {
     Json(logging::get_exception_log_path())
 }
 
-#[get("/reports/zip-file-name/<name>")]
-fn get_report_zip_file_name(name: &str) -> String {
-    report::zip_file_name(name)
+#[get("/reports/no-reset-system-settings-path", format = "json")]
+fn get_no_reset_system_settings_path() -> Json<PathBuf> {
+    Json(report::get_no_reset_system_settings_path())
+}
+
+#[get("/reports/file-name/<name>?<format>")]
+fn get_report_file_name(name: &str, format: &str) -> Result<String, (Status, String)> {
+    let format = ArchiveFormat::from_str(format).map_err(|err| (Status::BadRequest, err.to_string()))?;
+    Ok(report::report_file_name(name, format))
 }
 
 #[get("/file-exists/<path..>", format = "json")]
 fn get_file_exists(path: PathBuf) -> &'static str {
-    if path.exists() { "true" } else { "false" }
+    if Path::new("/").join(path).exists() { "true" } else { "false" }
 }
 
 // TODO: protections
 #[delete("/delete-file/<path..>")]
 fn delete_file(path: PathBuf) -> std::io::Result<()> {
-    std::fs::remove_file(path)
+    std::fs::remove_file(Path::new("/").join(path))
 }
 
 #[get("/hwconfig/path", format = "json")]
@@ -100,6 +160,70 @@ fn get_hwconfig_path() -> Json<Option<PathBuf>> {
     Json(hwconfig::get_path())
 }
 
+#[get("/hwconfig/valid-paths", format = "json")]
+fn get_hwconfig_valid_paths() -> Json<Vec<PathBuf>> {
+    Json(hwconfig::valid_paths())
+}
+
+#[get("/hwconfig/text/<path..>", format = "json")]
+fn get_hwconfig_text(path: PathBuf) -> Json<Option<String>> {
+    Json(hwconfig::read_from(&Path::new("/").join(path)))
+}
+
+#[post("/hwconfig/text/<path..>", data = "<text>")]
+fn set_hwconfig_text(path: PathBuf, text: String) -> Result<Json<Option<String>>, Status> {
+    hwconfig::set_text(&Path::new("/").join(path), &text)
+        .map(Json)
+        .map_err(|_| Status::InternalServerError)
+}
+
+#[get("/reports/inspect?<zip>")]
+fn get_report_inspect(zip: &str) -> Result<Json<ReportInfo>, (Status, String)> {
+    report_archive::inspect(Path::new(zip))
+        .map(Json)
+        .map_err(|err| (Status::NotFound, err.to_string()))
+}
+
+#[get("/reports/file?<zip>&<name>")]
+fn get_report_file(zip: &str, name: &str) -> Result<String, (Status, String)> {
+    report_archive::read_text(Path::new(zip), name).map_err(|err| (Status::NotFound, err.to_string()))
+}
+
+#[get("/hwconfig/source?<spec>")]
+fn get_hwconfig_source(spec: &str) -> Result<String, (Status, String)> {
+    hwconfig_diff::read_source(spec).map_err(|err| (Status::NotFound, err.to_string()))
+}
+
+#[get("/hwconfig/snapshots", format = "json")]
+fn get_hwconfig_snapshots() -> Json<Vec<Snapshot>> {
+    Json(hwconfig::list_snapshots())
+}
+
+#[get("/hwconfig/snapshots/<name>", format = "json")]
+fn get_hwconfig_snapshot(name: &str) -> Json<Option<String>> {
+    Json(hwconfig::read_snapshot(name))
+}
+
+#[post("/hwconfig/snapshots/<name>", data = "<text>")]
+fn save_hwconfig_snapshot(name: &str, text: String) -> Status {
+    match hwconfig::save_snapshot(name, &text) {
+        Ok(_) => {Status::Ok}
+        Err(_) => {Status::InternalServerError}
+    }
+}
+
+#[post("/hwconfig/known-good")]
+fn mark_hwconfig_known_good() -> Result<Json<PathBuf>, (Status, String)> {
+    hwconfig::mark_known_good()
+        .map(Json)
+        .map_err(|err| (Status::InternalServerError, err.to_string()))
+}
+
+#[get("/hwconfig/profiles", format = "json")]
+fn get_hwconfig_profiles() -> Json<Vec<Profile>> {
+    Json(hwconfig_profiles::list_profiles())
+}
+
 #[get("/versions/installed", format = "json")]
 fn get_versions_installed_version() -> Json<Option<String>> {
     Json(versions::installed_version())
@@ -114,22 +238,42 @@ fn get_versions_download_dir(branch: String) -> Json<PathBuf> {
 pub fn rocket() -> _ {
     rocket::build().mount("/", rocket::routes![
         get_cwd,
+        get_config_resolution,
         get_logging_config_path,
         get_logging_valid_paths,
         get_logging_code_path,
         get_logging_config,
-        get_logging_log_path_from_current_config,
+        get_logging_log_files,
         set_logging_config,
         get_logging_template,
         get_ion_diagnostics_config,
         set_ion_diagnostics_config,
+        get_ion_diagnostics_debug_dir,
+        get_ion_diagnostics_catalog,
         create_report,
+        get_stored_reports,
+        get_stored_report,
+        delete_stored_report,
+        get_report_profile,
+        set_report_profile,
         get_data_dir_state_file_paths,
         get_exception_log_path,
-        get_report_zip_file_name,
+        get_no_reset_system_settings_path,
+        get_report_file_name,
         get_file_exists,
         delete_file,
         get_hwconfig_path,
+        get_hwconfig_valid_paths,
+        get_hwconfig_text,
+        set_hwconfig_text,
+        get_report_inspect,
+        get_report_file,
+        get_hwconfig_source,
+        get_hwconfig_snapshots,
+        get_hwconfig_snapshot,
+        save_hwconfig_snapshot,
+        mark_hwconfig_known_good,
+        get_hwconfig_profiles,
         get_versions_installed_version,
         get_versions_download_dir
     ])
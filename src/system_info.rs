@@ -0,0 +1,350 @@
+use crate::common::format_bytes;
+use crate::{logging, report, versions};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Describes the machine a report was created on. Anything that can't be determined is left empty.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SystemInfo {
+    pub os_name: Option<String>,
+    pub os_version: Option<String>,
+    pub kernel: Option<String>,
+    pub cpu_model: Option<String>,
+    /// Logical cores.
+    pub cpu_cores: Option<usize>,
+    pub total_memory: Option<u64>,
+    pub free_memory: Option<u64>,
+    pub disks: Vec<DiskSpace>,
+    pub uptime_seconds: Option<u64>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub utc_offset: String,
+    pub environment: BTreeMap<String, String>,
+    /// PATH entries mentioning Keysight.
+    pub keysight_path_entries: Vec<String>,
+    pub siggen_processes: Vec<Process>,
+}
+
+/// Free space on the volume holding `path`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DiskSpace {
+    pub label: String,
+    pub path: PathBuf,
+    pub free: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Process {
+    pub pid: u32,
+    pub name: String,
+}
+
+pub fn collect() -> SystemInfo {
+    let mut volumes = vec![("log".to_string(), logging::get_log_path_from_current_config())];
+    if let Some(dir) = report::get_data_dir() {
+        volumes.push(("data".to_string(), dir));
+    }
+    let mut info = platform::collect(&volumes);
+    info.cpu_cores = std::thread::available_parallelism().map(|x| x.get()).ok();
+    info.utc_offset = chrono::Local::now().format("%:z").to_string();
+    for name in ENVIRONMENT_VARIABLES {
+        if let Ok(value) = std::env::var(name) {
+            info.environment.insert(name.to_string(), value);
+        }
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        info.keysight_path_entries = std::env::split_paths(&path)
+            .map(|x| x.display().to_string())
+            .filter(|x| x.to_lowercase().contains("keysight"))
+            .collect();
+    }
+    info
+}
+
+impl SystemInfo {
+    /// Lines for summary.txt.
+    pub fn summary(&self) -> Vec<String> {
+        let unknown = || "Unknown".to_string();
+        let size = |x: Option<u64>| x.map_or_else(unknown, format_bytes);
+        let mut lines = vec![
+            format!(
+                "OS: {} {}",
+                self.os_name.clone().unwrap_or_else(unknown),
+                self.os_version.as_deref().unwrap_or_default()
+            ),
+            format!(
+                "CPU: {} ({} cores)",
+                self.cpu_model.clone().unwrap_or_else(unknown),
+                self.cpu_cores.map_or_else(unknown, |x| x.to_string())
+            ),
+            format!("Memory: {} free of {}", size(self.free_memory), size(self.total_memory)),
+            format!(
+                "Free Disk Space: {}",
+                self.disks
+                    .iter()
+                    .map(|disk| format!("{} {}", disk.label, size(disk.free)))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            format!("Uptime: {}", self.uptime_seconds.map_or_else(unknown, format_duration)),
+            format!(
+                "Locale: {}, Time Zone: {} (UTC{})",
+                self.locale.clone().unwrap_or_else(unknown),
+                self.timezone.clone().unwrap_or_else(unknown),
+                self.utc_offset
+            ),
+            format!(
+                "SigGen Processes: {}",
+                match self.siggen_processes.is_empty() {
+                    true => "None".to_string(),
+                    false => self
+                        .siggen_processes
+                        .iter()
+                        .map(|x| format!("{} ({})", x.name, x.pid))
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                }
+            ),
+        ];
+        if let Some(kernel) = &self.kernel {
+            lines.insert(1, format!("Kernel: {}", kernel));
+        }
+        lines
+    }
+}
+
+fn format_duration(seconds: u64) -> String {
+    format!("{}d {}h {}m", seconds / 86400, seconds % 86400 / 3600, seconds % 3600 / 60)
+}
+
+/// SigGen's executables are named `Keysight.PathWave.SG*` and installed below a `SignalGenerator` directory, or
+/// the instrument directory on instruments. The installer, `Keysight.PathWave.SG.Setup_<version>.exe`, is left out.
+fn is_siggen(name: &str, path: Option<&str>) -> bool {
+    let name = name.to_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    if name.starts_with("keysight.pathwave.sg.setup") {
+        return false;
+    }
+    let installed = path.map(|x| x.to_lowercase().replace('\\', "/")).is_some_and(|path| {
+        path.contains("/signalgenerator/") || path.starts_with(&format!("{}/", versions::INSTRUMENT_DIR))
+    });
+    name.starts_with("keysight.pathwave.sg") || installed
+}
+
+/// The nearest existing ancestor, since the log directory may not have been created yet.
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|x| x.exists())
+}
+
+#[cfg(windows)]
+mod platform {
+    use crate::system_info::{existing_ancestor, is_siggen, DiskSpace, Process, SystemInfo};
+    use serde::Deserialize;
+    use std::path::PathBuf;
+
+    #[derive(Deserialize, Default)]
+    #[serde(rename_all = "PascalCase", default)]
+    struct Query {
+        caption: Option<String>,
+        version: Option<String>,
+        cpu: Option<String>,
+        total_memory_kb: Option<u64>,
+        free_memory_kb: Option<u64>,
+        uptime_seconds: Option<u64>,
+        culture: Option<String>,
+        time_zone: Option<String>,
+        disks: Vec<QueryDisk>,
+        processes: Vec<QueryProcess>,
+    }
+
+    /// One per volume, in order, so a failed query can't shift the others.
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct QueryDisk {
+        free: Option<u64>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct QueryProcess {
+        id: u32,
+        name: String,
+        path: Option<String>,
+    }
+
+    /// One PowerShell invocation, as starting it dominates the time taken.
+    pub fn collect(volumes: &[(String, PathBuf)]) -> SystemInfo {
+        let roots: Vec<String> = volumes
+            .iter()
+            .map(|(_, path)| {
+                existing_ancestor(path)
+                    .and_then(|x| x.ancestors().last())
+                    .map(|x| x.display().to_string().replace('\'', "''"))
+                    .unwrap_or_default()
+            })
+            .collect();
+        let script = format!(
+            "$os = Get-CimInstance Win32_OperatingSystem; \
+             $disks = @({}) | ForEach-Object {{ $free = $null; \
+                 try {{ $free = (New-Object System.IO.DriveInfo $_).AvailableFreeSpace }} catch {{}}; \
+                 [pscustomobject]@{{ Free = $free }} }}; \
+             [pscustomobject]@{{ \
+                 Caption = $os.Caption; Version = $os.Version; \
+                 Cpu = (Get-CimInstance Win32_Processor | Select-Object -First 1).Name; \
+                 TotalMemoryKb = [uint64]$os.TotalVisibleMemorySize; FreeMemoryKb = [uint64]$os.FreePhysicalMemory; \
+                 UptimeSeconds = [uint64]((Get-Date) - $os.LastBootUpTime).TotalSeconds; \
+                 Culture = (Get-Culture).Name; TimeZone = (Get-TimeZone).Id; Disks = @($disks); \
+                 Processes = @(Get-Process | Select-Object Id, Name, Path) \
+             }} | ConvertTo-Json -Compress -Depth 3",
+            roots.iter().map(|x| format!("'{}'", x)).collect::<Vec<String>>().join(", ")
+        );
+        let query: Query = std::process::Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", script.as_str()])
+            .output()
+            .ok()
+            .and_then(|output| serde_json::from_slice(&output.stdout).ok())
+            .unwrap_or_default();
+        SystemInfo {
+            os_name: query.caption,
+            os_version: query.version,
+            cpu_model: query.cpu.map(|x| x.trim().to_string()),
+            total_memory: query.total_memory_kb.map(|x| x * 1024),
+            free_memory: query.free_memory_kb.map(|x| x * 1024),
+            disks: volumes
+                .iter()
+                .enumerate()
+                .map(|(i, (label, path))| DiskSpace {
+                    label: label.clone(),
+                    path: path.clone(),
+                    free: query.disks.get(i).and_then(|x| x.free),
+                })
+                .collect(),
+            uptime_seconds: query.uptime_seconds,
+            locale: query.culture,
+            timezone: query.time_zone,
+            siggen_processes: query
+                .processes
+                .into_iter()
+                .filter(|x| x.id != std::process::id() && is_siggen(&x.name, x.path.as_deref()))
+                .map(|x| Process { pid: x.id, name: x.name })
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod platform {
+    use crate::system_info::{existing_ancestor, is_siggen, DiskSpace, Process, SystemInfo};
+    use std::path::PathBuf;
+
+    pub fn collect(volumes: &[(String, PathBuf)]) -> SystemInfo {
+        let os_release = read("/etc/os-release").unwrap_or_default();
+        let os_release_value = |key: &str| {
+            os_release
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+                .map(|x| x.trim_matches('"').to_string())
+        };
+        let meminfo = read("/proc/meminfo").unwrap_or_default();
+        let memory = |key: &str| {
+            meminfo
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+                .and_then(|x| x.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+                .map(|x| x * 1024)
+        };
+        SystemInfo {
+            os_name: os_release_value("NAME").or_else(|| Some(std::env::consts::OS.to_string())),
+            os_version: os_release_value("VERSION_ID"),
+            kernel: read("/proc/sys/kernel/osrelease").map(|x| x.trim().to_string()),
+            cpu_model: read("/proc/cpuinfo").and_then(|x| {
+                x.lines()
+                    .find(|line| line.starts_with("model name"))
+                    .and_then(|line| line.split_once(':'))
+                    .map(|(_, value)| value.trim().to_string())
+            }),
+            total_memory: memory("MemTotal"),
+            free_memory: memory("MemAvailable"),
+            disks: volumes
+                .iter()
+                .map(|(label, path)| DiskSpace {
+                    label: label.clone(),
+                    path: path.clone(),
+                    free: existing_ancestor(path).and_then(free_space),
+                })
+                .collect(),
+            uptime_seconds: read("/proc/uptime")
+                .and_then(|x| x.split_whitespace().next()?.parse::<f64>().ok())
+                .map(|x| x as u64),
+            locale: ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .iter()
+                .find_map(|x| std::env::var(x).ok().filter(|x| !x.is_empty())),
+            timezone: std::env::var("TZ").ok().or_else(|| read("/etc/timezone").map(|x| x.trim().to_string())),
+            siggen_processes: processes(),
+            ..Default::default()
+        }
+    }
+
+    fn read(path: &str) -> Option<String> {
+        std::fs::read_to_string(path).ok()
+    }
+
+    /// Parses `df -Pk`, which is the same on every POSIX system.
+    fn free_space(path: &std::path::Path) -> Option<u64> {
+        let output = std::process::Command::new("df").arg("-Pk").arg(path).output().ok()?;
+        let text = String::from_utf8_lossy(&output.stdout).to_string();
+        let available = text.lines().nth(1)?.split_whitespace().nth(3)?.parse::<u64>().ok()?;
+        Some(available * 1024)
+    }
+
+    fn processes() -> Vec<Process> {
+        let entries = match std::fs::read_dir("/proc") {
+            Ok(entries) => entries,
+            Err(_) => return vec![],
+        };
+        let mut processes: Vec<Process> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+                // comm is cut to 15 characters, the command line has the whole executable path.
+                let cmdline = std::fs::read(entry.path().join("cmdline")).ok()?;
+                let path = String::from_utf8_lossy(cmdline.split(|x| *x == 0).next()?).to_string();
+                let name = match path.rsplit('/').next() {
+                    Some(name) if !name.is_empty() => name.to_string(),
+                    _ => std::fs::read_to_string(entry.path().join("comm")).ok()?.trim().to_string(),
+                };
+                (pid != std::process::id() && is_siggen(&name, Some(&path))).then_some(Process { pid, name })
+            })
+            .collect();
+        processes.sort_by_key(|x| x.pid);
+        processes
+    }
+}
+
+const ENVIRONMENT_VARIABLES: [&str; 1] = ["ION_DEBUG_DIR"];
+
+#[cfg(test)]
+mod tests {
+    use crate::system_info::{format_duration, is_siggen};
+
+    #[test]
+    fn formatting() {
+        assert_eq!(format_duration(93_784), "1d 2h 3m");
+    }
+
+    #[test]
+    fn siggen_processes() {
+        assert!(is_siggen("Keysight.PathWave.SG", None));
+        assert!(is_siggen("Keysight.PathWave.SG.Server.exe", None));
+        assert!(is_siggen(
+            "instrument",
+            Some(r"C:\Program Files\Keysight\PathWave\SignalGenerator\instrument\instrument.exe")
+        ));
+        assert!(is_siggen("sghal", Some("/firmware/user/bin/instrument/sghal")));
+        assert!(!is_siggen("Keysight.PathWave.SG.Setup_1.2.3.exe", Some(r"C:\Users\me\Downloads\x.exe")));
+        assert!(!is_siggen("explorer", Some(r"C:\Windows\explorer.exe")));
+        assert!(!is_siggen("bash", Some("/usr/bin/bash")));
+    }
+}
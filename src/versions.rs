@@ -1,26 +1,32 @@
+use crate::common::format_bytes;
+use crate::credentials;
 use crate::gui_state::VersionsTypes;
+use crate::remotes::{self, Remote};
+use crate::report_encryption;
+use crate::report_upload::UploadProperties;
 use eframe::epi;
 use serde::{Deserialize, Serialize};
+use sha2::Digest;
 use std::cmp::Ordering;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+#[derive(Clone)]
 pub struct VersionsClient {
     client: Arc<reqwest::blocking::Client>,
+    pub remote_name: String,
+    pub remote: Remote,
 }
 
+/// With the remote picked at startup.
 impl Default for VersionsClient {
     fn default() -> Self {
-        Self {
-            client: Arc::from(
-                reqwest::blocking::Client::builder()
-                    .timeout(Duration::from_secs(1000))
-                    .build()
-                    .expect("Unable to create web client"),
-            ),
-        }
+        let (name, remote) = remotes::selected();
+        Self::new(name, remote)
     }
 }
 
@@ -66,6 +72,48 @@ impl Default for RequestStatus {
     }
 }
 
+/// Where a report upload is, shared between the uploading thread and whatever shows it.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub enum UploadStatus {
+    #[default]
+    Idle,
+    Hashing,
+    Uploading {
+        sent: u64,
+        total: u64,
+        attempt: u32,
+    },
+    /// Waiting to try again after a transient failure.
+    Retrying {
+        attempt: u32,
+        error: String,
+    },
+    /// Comparing the checksums Artifactory stored with the local ones.
+    Verifying,
+    Success,
+    Error(String),
+}
+
+impl fmt::Display for UploadStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UploadStatus::Idle => write!(f, "Not started"),
+            UploadStatus::Hashing => write!(f, "Computing checksums"),
+            UploadStatus::Uploading { sent, total, attempt } => {
+                write!(f, "Uploading {} of {}", format_bytes(*sent), format_bytes(*total))?;
+                if *attempt > 1 {
+                    write!(f, ", attempt {}", attempt)?;
+                }
+                Ok(())
+            }
+            UploadStatus::Retrying { attempt, error } => write!(f, "Attempt {} failed, retrying: {}", attempt, error),
+            UploadStatus::Verifying => write!(f, "Verifying checksums"),
+            UploadStatus::Success => write!(f, "Upload complete"),
+            UploadStatus::Error(error) => write!(f, "Upload failed: {}", error),
+        }
+    }
+}
+
 #[derive(Debug, Default, PartialEq)]
 pub struct SemVer {
     pub major: u16,
@@ -184,9 +232,105 @@ mod tests {
         b = make_semver(1, 2, 3, Some(9));
         assert_eq!(a.partial_cmp(&b), Some(Ordering::Equal));
     }
+
+    #[test]
+    fn upload_retry_and_verify() {
+        use crate::report_upload::UploadProperties;
+        use sha2::{Digest, Sha256};
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::sync::{Arc, Mutex};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let remote = crate::remotes::Remote {
+            url: format!("http://{}", listener.local_addr().unwrap()),
+            ..Default::default()
+        };
+        let client = versions::VersionsClient::new("stand-in".to_string(), remote);
+        let contents = b"not really a zip";
+        let sha256 = format!("{:x}", Sha256::digest(contents));
+        let stored = format!(r#"{{"checksums": {{"sha256": "{}"}}}}"#, sha256);
+        // A stand-in for Artifactory: fails the first upload, accepts the second and then reports the checksum.
+        let server = std::thread::spawn(move || {
+            let mut requests = vec![];
+            for response in ["503 Service Unavailable", "201 Created", "200 OK"] {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut lines = vec![];
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    lines.push(line.trim().to_string());
+                }
+                let length = lines
+                    .iter()
+                    .find_map(|x| x.to_lowercase().strip_prefix("content-length: ").map(|x| x.parse().unwrap()))
+                    .unwrap_or(0);
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let body_out = if response == "200 OK" { stored.as_str() } else { "" };
+                write!(
+                    reader.get_mut(),
+                    "HTTP/1.1 {}\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+                    response,
+                    body_out.len(),
+                    body_out
+                )
+                .unwrap();
+                requests.push((lines, body));
+            }
+            requests
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sgt-upload.zip");
+        std::fs::write(&path, contents).unwrap();
+        let properties = UploadProperties {
+            ticket: Some("SG-1234".to_string()),
+            ..Default::default()
+        };
+        let status = Arc::new(Mutex::new(versions::UploadStatus::Idle));
+        let result = client.upload_report(&path, &properties, status.clone(), None).unwrap().join().unwrap();
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(*status.lock().unwrap(), versions::UploadStatus::Success);
+
+        let requests = server.join().unwrap();
+        let file_name = path.file_name().unwrap().to_string_lossy();
+        let (lines, body) = &requests[1];
+        assert_eq!(
+            lines[0],
+            format!("PUT /generic-local-pwsg/siggen/reports/{};sgt.ticket=SG-1234 HTTP/1.1", file_name)
+        );
+        assert!(lines.contains(&format!("x-checksum-sha256: {}", sha256)));
+        assert_eq!(body, contents);
+        assert!(requests[2].0[0].starts_with(&format!("GET /api/storage/generic-local-pwsg/siggen/reports/{}", file_name)));
+    }
 }
 
 impl VersionsClient {
+    /// Authenticates with the credential saved with `sgt auth login` for this remote name and URL, if any.
+    pub fn new(remote_name: String, remote: Remote) -> Self {
+        let headers = credentials::load(&remote_name, &remote.url)
+            .and_then(|credential| credential.map(|(x, _)| x.headers()).transpose())
+            .unwrap_or_else(|err| {
+                eprintln!("Requests to {} are anonymous: {}", remote_name, err);
+                None
+            });
+        Self {
+            client: Arc::from(
+                reqwest::blocking::Client::builder()
+                    .timeout(Duration::from_secs(1000))
+                    .default_headers(headers.unwrap_or_default())
+                    .build()
+                    .expect("Unable to create web client"),
+            ),
+            remote_name,
+            remote,
+        }
+    }
+
     pub fn download_package(
         &self,
         which: &VersionsTypes,
@@ -196,10 +340,10 @@ impl VersionsClient {
         frame: epi::Frame,
     ) -> anyhow::Result<()> {
         let segments = match which {
-            VersionsTypes::Packages => package_segments(),
-            VersionsTypes::Installers => installer_segments(),
+            VersionsTypes::Packages => &self.remote.packages,
+            VersionsTypes::Installers => &self.remote.installers,
         };
-        let url = format!("{}/{}/{}/{}", BASE_FILE_URL, segments, branch, file_name);
+        let url = format!("{}/{}/{}/{}", self.remote.url, segments, branch, file_name);
 
         let destination_dir = download_dir(branch);
         let file_name = file_name.to_string();
@@ -222,44 +366,54 @@ impl VersionsClient {
         Ok(())
     }
 
-    // TODO: backend: handle status/frame
+    /// Uploads with the properties attached, retrying transient failures, then checks that Artifactory stored what
+    /// was sent. The thread's result says whether all of that succeeded. Progress goes to `status`, and `frame` is
+    /// repainted on each change if given. The file is local, reports created by a backend are fetched first.
     pub fn upload_report(
         &self,
         path: &Path,
-        status: Option<Arc<Mutex<RequestStatus>>>,
+        properties: &UploadProperties,
+        status: Arc<Mutex<UploadStatus>>,
         frame: Option<epi::Frame>,
-    ) -> anyhow::Result<std::thread::JoinHandle<()>> {
-        let url = format!(
-            "{}/{}/{}",
-            BASE_FILE_URL,
-            report_segments(),
-            path.file_name().unwrap().to_string_lossy()
-        );
-        let client = self.client.clone();
-        let path = PathBuf::from(path);
+    ) -> anyhow::Result<std::thread::JoinHandle<anyhow::Result<()>>> {
+        report_encryption::check_upload(path)?;
+        let upload = Upload {
+            client: self.client.clone(),
+            url: format!("{}{}", self.report_url(path), properties.matrix_params()),
+            storage_url: self.storage_url(&format!(
+                "{}/{}",
+                self.remote.reports,
+                path.file_name().unwrap().to_string_lossy()
+            )),
+            path: PathBuf::from(path),
+            status,
+            frame,
+        };
         let handle = std::thread::spawn(move || {
-            if let Some(ref status) = status {
-                *status.lock().unwrap() = RequestStatus::InProgress;
-            }
-            match upload_report_internal(&client, &url, &path) {
-                Ok(_) if status.is_some() => {
-                    *status.unwrap().lock().unwrap() = RequestStatus::Success;
-                }
-                Err(_) if status.is_some() => {
-                    *status.unwrap().lock().unwrap() = RequestStatus::Error;
-                }
-                _ => {}
-            }
-            if let Some(frame) = frame {
-                frame.request_repaint();
-            }
+            let result = upload.run();
+            upload.set_status(match &result {
+                Ok(_) => UploadStatus::Success,
+                Err(err) => UploadStatus::Error(err.to_string()),
+            });
+            result
         });
 
         Ok(handle)
     }
 
-    pub fn get_packages_info(&self, branch: &str) -> Vec<FileInfo> {
-        self.get_info(branch, &package_segments())
+    /// Where a report is uploaded to.
+    pub fn report_url(&self, path: &Path) -> String {
+        format!(
+            "{}/{}/{}",
+            self.remote.url,
+            self.remote.reports,
+            path.file_name().unwrap().to_string_lossy()
+        )
+    }
+
+    pub fn get_packages_info(&self, branch: &str) -> anyhow::Result<Vec<FileInfo>> {
+        Ok(self
+            .get_info(branch, &self.remote.packages)?
             .into_iter()
             .filter_map(|full_name| {
                 let mut split: VecDeque<String> = full_name
@@ -281,11 +435,12 @@ impl VersionsClient {
                     None
                 }
             })
-            .collect()
+            .collect())
     }
 
-    pub fn get_installers_info(&self, branch: &str) -> Vec<FileInfo> {
-        self.get_info(branch, &installer_segments())
+    pub fn get_installers_info(&self, branch: &str) -> anyhow::Result<Vec<FileInfo>> {
+        Ok(self
+            .get_info(branch, &self.remote.installers)?
             .into_iter()
             .filter_map(|full_name| {
                 let mut split: VecDeque<String> = full_name
@@ -306,34 +461,115 @@ impl VersionsClient {
                     None
                 }
             })
-            .collect()
+            .collect())
     }
 
-    fn get_info(&self, branch: &str, segments: &str) -> Vec<String> {
-        parse_children(
-            self.api_request(&format!("{}/{}", segments, branch))
-                .unwrap_or_default(),
-        )
+    fn get_info(&self, branch: &str, segments: &str) -> anyhow::Result<Vec<String>> {
+        Ok(parse_children(self.api_request(&format!("{}/{}", segments, branch))?))
     }
 
-    pub fn get_packages_branch_names(&self) -> Vec<String> {
-        self.get_branch_names(&package_segments())
+    pub fn get_packages_branch_names(&self) -> anyhow::Result<Vec<String>> {
+        self.get_branch_names(&self.remote.packages)
     }
 
-    pub fn get_installers_branch_names(&self) -> Vec<String> {
-        self.get_branch_names(&installer_segments())
+    pub fn get_installers_branch_names(&self) -> anyhow::Result<Vec<String>> {
+        self.get_branch_names(&self.remote.installers)
     }
 
-    fn get_branch_names(&self, segments: &str) -> Vec<String> {
-        parse_children(self.api_request(segments).unwrap_or_default())
+    fn get_branch_names(&self, segments: &str) -> anyhow::Result<Vec<String>> {
+        Ok(parse_children(self.api_request(segments)?))
     }
 
-    fn api_request(&self, segments: &str) -> Option<ArtifactoryDirectory> {
-        let request = self.client.get(format!("{}/{}", BASE_API_URL, segments));
-        serde_json::from_str(&request.send().ok()?.text().unwrap_or_default()).ok()?
+    fn api_request(&self, segments: &str) -> anyhow::Result<ArtifactoryDirectory> {
+        let response = check_status(self.client.get(self.storage_url(segments)).send()?)?;
+        Ok(serde_json::from_str(&response.text()?)?)
+    }
+
+    /// Artifactory's storage API, which describes files and folders rather than serving them.
+    fn storage_url(&self, segments: &str) -> String {
+        format!("{}/api/storage/{}", self.remote.url, segments)
+    }
+
+    /// Uploaded reports, newest first by the date in their name. Filtering by host takes a request per report for
+    /// its properties, so `since` is applied first.
+    pub fn list_reports(&self, host: Option<&str>, since: Option<chrono::NaiveDate>) -> anyhow::Result<Vec<RemoteReport>> {
+        let mut directory = self.api_request(&self.remote.reports)?;
+        directory.children.retain(|x| !x.folder);
+        let mut reports: Vec<RemoteReport> = parse_children(directory)
+            .into_iter()
+            .map(|name| RemoteReport {
+                date: report_date(&name),
+                name,
+                ..Default::default()
+            })
+            .filter(|x| since.is_none_or(|since| x.date.is_some_and(|date| date >= since)))
+            .collect();
+        if let Some(host) = host {
+            for report in reports.iter_mut() {
+                report.properties = self.report_properties(&report.name)?;
+            }
+            reports.retain(|x| x.properties.get("sgt.host").is_some_and(|x| x.eq_ignore_ascii_case(host)));
+        }
+        reports.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.name.cmp(&b.name)));
+        Ok(reports)
+    }
+
+    /// The item properties set when the report was uploaded. Values set more than once are joined with commas.
+    pub fn report_properties(&self, name: &str) -> anyhow::Result<BTreeMap<String, String>> {
+        let url = format!("{}?properties", self.storage_url(&format!("{}/{}", self.remote.reports, name)));
+        let response = self.client.get(url).send()?;
+        // Artifactory answers 404 for an item without properties.
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(BTreeMap::new());
+        }
+        let info: serde_json::Value = serde_json::from_str(&check_status(response)?.text()?)?;
+        Ok(info["properties"]
+            .as_object()
+            .map(|properties| {
+                properties
+                    .iter()
+                    .map(|(key, values)| {
+                        let values: Vec<&str> = values.as_array().into_iter().flatten().filter_map(|x| x.as_str()).collect();
+                        (key.clone(), values.join(","))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Downloads the report to `destination` and checks it against the checksum Artifactory has for it.
+    pub fn download_report(&self, name: &str, destination: &Path) -> anyhow::Result<()> {
+        let partial = PathBuf::from(format!("{}.partial", destination.display()));
+        let result = (|| -> anyhow::Result<()> {
+            let mut out = std::fs::File::create(&partial)?;
+            let url = format!("{}/{}/{}", self.remote.url, self.remote.reports, name);
+            check_status(self.client.get(url).send()?)?.copy_to(&mut out)?;
+            drop(out);
+            let storage_url = self.storage_url(&format!("{}/{}", self.remote.reports, name));
+            verify_checksums(&self.client, &storage_url, &Checksums::of(&partial)?)?;
+            std::fs::rename(&partial, destination)?;
+            Ok(())
+        })();
+        if result.is_err() {
+            let _ = std::fs::remove_file(&partial);
+        }
+        result
     }
 }
 
+/// A report on Artifactory. Properties are only filled in when they were needed for filtering.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RemoteReport {
+    pub name: String,
+    /// From the `<date>_<name>` file name reports are created with.
+    pub date: Option<chrono::NaiveDate>,
+    pub properties: BTreeMap<String, String>,
+}
+
+fn report_date(name: &str) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(name.get(..10)?, "%Y-%m-%d").ok()
+}
+
 fn parse_children(response: ArtifactoryDirectory) -> Vec<String> {
     response
         .children
@@ -357,39 +593,177 @@ fn download_internal(
 ) -> anyhow::Result<()> {
     std::fs::create_dir_all(destination_dir)?;
     let mut out = std::fs::File::create(format!("{}/{}", destination_dir.display(), file_name))?;
-    client.get(url).send()?.error_for_status()?.copy_to(&mut out)?;
+    check_status(client.get(url).send()?)?.copy_to(&mut out)?;
     Ok(())
 }
 
-fn upload_report_internal(client: &Arc<reqwest::blocking::Client>, url: &str, path: &Path) -> anyhow::Result<()> {
-    let file = std::fs::File::open(path)?;
-    client.put(url).body(file).send()?.error_for_status()?;
+struct Upload {
+    client: Arc<reqwest::blocking::Client>,
+    url: String,
+    /// Artifactory's storage API for the uploaded file, which reports the checksums it stored.
+    storage_url: String,
+    path: PathBuf,
+    status: Arc<Mutex<UploadStatus>>,
+    frame: Option<epi::Frame>,
+}
+
+impl Upload {
+    fn run(&self) -> anyhow::Result<()> {
+        self.set_status(UploadStatus::Hashing);
+        let checksums = Checksums::of(&self.path)?;
+        let mut attempt = 1;
+        loop {
+            match self.put(&checksums, attempt) {
+                Ok(_) => break,
+                Err(err) if attempt < MAX_UPLOAD_ATTEMPTS && is_transient(&err) => {
+                    self.set_status(UploadStatus::Retrying {
+                        attempt,
+                        error: err.to_string(),
+                    });
+                    std::thread::sleep(UPLOAD_RETRY_DELAY * 2u32.pow(attempt - 1));
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        self.set_status(UploadStatus::Verifying);
+        verify_checksums(&self.client, &self.storage_url, &checksums)
+    }
+
+    fn put(&self, checksums: &Checksums, attempt: u32) -> anyhow::Result<()> {
+        let file = std::fs::File::open(&self.path)?;
+        let total = file.metadata()?.len();
+        let reader = ProgressReader {
+            file,
+            sent: 0,
+            total,
+            attempt,
+            status: self.status.clone(),
+            frame: self.frame.clone(),
+        };
+        let response = self
+            .client
+            .put(&self.url)
+            .header("X-Checksum-Sha1", &checksums.sha1)
+            .header("X-Checksum-Sha256", &checksums.sha256)
+            .body(reqwest::blocking::Body::sized(reader, total))
+            .send()?;
+        check_status(response)?;
+        Ok(())
+    }
+
+    fn set_status(&self, status: UploadStatus) {
+        set_upload_status(&self.status, &self.frame, status);
+    }
+}
+
+fn set_upload_status(status: &Mutex<UploadStatus>, frame: &Option<epi::Frame>, value: UploadStatus) {
+    *status.lock().unwrap() = value;
+    if let Some(frame) = frame {
+        frame.request_repaint();
+    }
+}
+
+/// Compares the checksums Artifactory has for a file with the local copy's, SHA-256 if it has one.
+fn verify_checksums(client: &reqwest::blocking::Client, storage_url: &str, checksums: &Checksums) -> anyhow::Result<()> {
+    let text = check_status(client.get(storage_url).send()?)?.text()?;
+    let info: serde_json::Value = serde_json::from_str(&text)?;
+    let stored = &info["checksums"];
+    let (local, remote) = match stored["sha256"].as_str() {
+        Some(sha256) => (&checksums.sha256, sha256),
+        None => (&checksums.sha1, stored["sha1"].as_str().unwrap_or_default()),
+    };
+    if !local.eq_ignore_ascii_case(remote) {
+        return Err(anyhow::anyhow!("Checksum mismatch, {} locally and {} on Artifactory", local, remote));
+    }
     Ok(())
 }
 
-pub fn installed_version() -> Option<String> {
-    let package_json_path = if cfg!(windows) { r"C:\Program Files\Keysight\PathWave\SignalGenerator\instrument\package.json" } else { "/firmware/user/bin/instrument/package.json" };
+/// Says how to log in when Artifactory refuses the request, and is the response's error for other failures.
+fn check_status(response: reqwest::blocking::Response) -> anyhow::Result<reqwest::blocking::Response> {
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        return Err(anyhow::anyhow!(
+            "{}: authentication required for {}. Log in with sgt auth login.",
+            status,
+            response.url()
+        ));
+    }
+    Ok(response.error_for_status()?)
+}
 
-    std::fs::read_to_string(package_json_path)
-        .ok()
-        .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
-        .map(|json| json["version"].to_string().trim_matches('"').to_string())
+/// Connection problems, timeouts, server errors and rate limiting may go away by themselves.
+fn is_transient(err: &anyhow::Error) -> bool {
+    match err.downcast_ref::<reqwest::Error>() {
+        Some(err) => match err.status() {
+            Some(status) => status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS,
+            None => err.is_connect() || err.is_timeout() || err.is_request() || err.is_body(),
+        },
+        None => false,
+    }
 }
 
-pub fn package_segments() -> String {
-    format!(
-        "{}/{}",
-        "generic-local-pwsg/siggen",
-        if cfg!(windows) { "packages" } else { "packages-linux" }
-    )
+struct Checksums {
+    sha1: String,
+    sha256: String,
 }
 
-pub fn installer_segments() -> String {
-    "generic-local-boxer-releases/siggen".to_string()
+impl Checksums {
+    fn of(path: &Path) -> anyhow::Result<Self> {
+        let mut file = std::fs::File::open(path)?;
+        let mut sha1 = sha1::Sha1::new();
+        let mut sha256 = sha2::Sha256::new();
+        let mut buffer = vec![0; 64 << 10];
+        loop {
+            let n = file.read(&mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            sha1.update(&buffer[..n]);
+            sha256.update(&buffer[..n]);
+        }
+        Ok(Self {
+            sha1: format!("{:x}", sha1.finalize()),
+            sha256: format!("{:x}", sha256.finalize()),
+        })
+    }
+}
+
+/// Reports each chunk reqwest reads from the file as upload progress.
+struct ProgressReader {
+    file: std::fs::File,
+    sent: u64,
+    total: u64,
+    attempt: u32,
+    status: Arc<Mutex<UploadStatus>>,
+    frame: Option<epi::Frame>,
+}
+
+impl Read for ProgressReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.file.read(buf)?;
+        self.sent += n as u64;
+        let status = UploadStatus::Uploading {
+            sent: self.sent,
+            total: self.total,
+            attempt: self.attempt,
+        };
+        set_upload_status(&self.status, &self.frame, status);
+        Ok(n)
+    }
 }
 
-pub fn report_segments() -> String {
-    "generic-local-pwsg/siggen/reports".to_string()
+pub fn installed_version() -> Option<String> {
+    let package_json_path = if cfg!(windows) {
+        PathBuf::from(r"C:\Program Files\Keysight\PathWave\SignalGenerator\instrument\package.json")
+    } else {
+        Path::new(INSTRUMENT_DIR).join("package.json")
+    };
+
+    std::fs::read_to_string(package_json_path)
+        .ok()
+        .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+        .map(|json| json["version"].to_string().trim_matches('"').to_string())
 }
 
 pub fn develop_branch() -> String {
@@ -397,5 +771,8 @@ pub fn develop_branch() -> String {
 }
 
 pub const DEVELOP_BRANCH: &str = "develop";
-pub const BASE_FILE_URL: &str = "https://artifactory.it.keysight.com/artifactory";
-pub const BASE_API_URL: &str = "https://artifactory.it.keysight.com/artifactory/api/storage";
+/// Where SigGen is installed on instruments, the directory holding the package.json its version is read from.
+pub const INSTRUMENT_DIR: &str = "/firmware/user/bin/instrument";
+const MAX_UPLOAD_ATTEMPTS: u32 = 4;
+/// Doubled after each failed attempt.
+const UPLOAD_RETRY_DELAY: Duration = Duration::from_secs(1);